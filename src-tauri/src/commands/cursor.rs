@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use sqlx::{Postgres, Transaction};
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::commands::connection::{get_or_create_db_pool, AppState};
+use crate::db::postgres;
+use crate::models::{AppError, QueryResult};
+
+/// Each cursor has its own transaction, so they can all share one name.
+const CURSOR_NAME: &str = "bestgres_cursor";
+
+/// A cursor opened by `open_cursor`, held with its transaction until `close_cursor`.
+/// The transaction has its own lock so a FETCH doesn't hold up every other cursor.
+pub struct OpenCursor {
+    pub connection_id: String,
+    /// Taken when the cursor is closed; a fetch that was waiting for it then finds it gone.
+    pub tx: Arc<Mutex<Option<Transaction<'static, Postgres>>>>,
+}
+
+impl OpenCursor {
+    /// Roll back the cursor's transaction, after any fetch still running on it.
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        let tx = self.tx.lock().await.take();
+        match tx {
+            Some(tx) => tx.rollback().await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Close every open cursor on a connection, e.g. before its pools are closed.
+pub async fn discard_cursors(state: &AppState, connection_id: &str) {
+    let mut cursors = state.cursors.lock().await;
+    let ids: Vec<String> = cursors
+        .iter()
+        .filter(|(_, open)| open.connection_id == connection_id)
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in ids {
+        if let Some(open) = cursors.remove(&id) {
+            let _ = open.rollback().await;
+        }
+    }
+}
+
+/// Open a server-side cursor over a query. Rows are read with `fetch_cursor` from a
+/// stable snapshot, without re-running the query for each page as OFFSET paging does.
+/// The cursor holds a pooled connection until `close_cursor`.
+#[tauri::command]
+pub async fn open_cursor(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+) -> Result<String, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    let mut tx = pool.begin().await.map_err(AppError::from_sqlx)?;
+    postgres::declare_cursor(&mut tx, CURSOR_NAME, &sql).await?;
+
+    let cursor_id = uuid::Uuid::new_v4().to_string();
+    state
+        .cursors
+        .lock()
+        .await
+        .insert(
+            cursor_id.clone(),
+            OpenCursor {
+                connection_id,
+                tx: Arc::new(Mutex::new(Some(tx))),
+            },
+        );
+    Ok(cursor_id)
+}
+
+/// Fetch the next `count` rows from a cursor. Fewer than `count` rows means the end
+/// has been reached.
+#[tauri::command]
+pub async fn fetch_cursor(
+    state: State<'_, AppState>,
+    cursor_id: String,
+    count: i64,
+) -> Result<QueryResult, AppError> {
+    if count <= 0 {
+        return Err(AppError::database("Fetch count must be positive".into()));
+    }
+
+    let not_found = || AppError::database(format!("Cursor {} not found", cursor_id));
+    let tx = state
+        .cursors
+        .lock()
+        .await
+        .get(&cursor_id)
+        .map(|open| open.tx.clone())
+        .ok_or_else(not_found)?;
+    let mut tx = tx.lock().await;
+    let tx = tx.as_mut().ok_or_else(not_found)?;
+    postgres::fetch_cursor(tx, CURSOR_NAME, count).await
+}
+
+/// Close a cursor and end its transaction, returning the connection to the pool.
+#[tauri::command]
+pub async fn close_cursor(state: State<'_, AppState>, cursor_id: String) -> Result<(), AppError> {
+    let open = state
+        .cursors
+        .lock()
+        .await
+        .remove(&cursor_id)
+        .ok_or_else(|| AppError::database(format!("Cursor {} not found", cursor_id)))?;
+    // Read-only use; rolling back just releases the snapshot.
+    open.rollback().await.map_err(AppError::from_sqlx)
+}
@@ -0,0 +1,186 @@
+use std::io::Write;
+
+use serde_json::Value as JsonValue;
+use tauri::State;
+
+use crate::commands::connection::{ensure_sql_stays_read_only, get_or_create_db_pool, AppState};
+use crate::db::{export, postgres};
+use crate::models::{AppError, ExportFormat, ExportSectionResult, JsonExportFormat, QueryResult};
+
+fn write_export(writer: &mut impl Write, content: &str) -> Result<(), AppError> {
+    writer
+        .write_all(content.as_bytes())
+        .map_err(|e| AppError::Config(format!("Cannot write export file: {}", e)))
+}
+
+/// Render a result already on screen as text (TSV, Markdown, ...) for the clipboard.
+#[tauri::command]
+pub fn format_query_result(result: QueryResult, format: ExportFormat) -> String {
+    export::format_result(&result, format)
+}
+
+/// Run a query and write its rows to a CSV file as they arrive, so large results are
+/// never held in memory. Returns the number of rows written. NULL cells are empty fields.
+/// A query that returns no rows produces an empty file, since there's no row to read
+/// the column names from.
+#[tauri::command]
+pub async fn export_query_csv(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+    path: String,
+) -> Result<usize, AppError> {
+    ensure_sql_stays_read_only(&state, &connection_id, &sql).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+
+    let file = std::fs::File::create(&path)
+        .map_err(|e| AppError::Config(format!("Cannot create export file: {}", e)))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let mut header_written = false;
+
+    let row_count = postgres::for_each_row(&pool, &sql, |columns, row| {
+        if !header_written {
+            write_export(&mut writer, &format!("{}\n", export::csv_record(columns)))?;
+            header_written = true;
+        }
+        let cells: Vec<String> = row.iter().map(export::cell_to_text).collect();
+        write_export(&mut writer, &format!("{}\n", export::csv_record(&cells)))
+    })
+    .await?;
+
+    writer
+        .flush()
+        .map_err(|e| AppError::Config(format!("Cannot write export file: {}", e)))?;
+    Ok(row_count)
+}
+
+/// Run a query and write its rows to a file as JSON objects keyed by column name,
+/// streaming as with CSV export. `Array` writes a single JSON array; `Jsonl` writes one
+/// object per line. NULLs are written as `null`. Returns the number of rows written.
+#[tauri::command]
+pub async fn export_query_json(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+    path: String,
+    format: JsonExportFormat,
+) -> Result<usize, AppError> {
+    ensure_sql_stays_read_only(&state, &connection_id, &sql).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+
+    let file = std::fs::File::create(&path)
+        .map_err(|e| AppError::Config(format!("Cannot create export file: {}", e)))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let mut first = true;
+
+    if format == JsonExportFormat::Array {
+        write_export(&mut writer, "[")?;
+    }
+    let row_count = postgres::for_each_row(&pool, &sql, |columns, row| {
+        let object = export::row_object(columns, row);
+        let line = match format {
+            JsonExportFormat::Array if first => format!("\n  {}", object),
+            JsonExportFormat::Array => format!(",\n  {}", object),
+            JsonExportFormat::Jsonl => format!("{}\n", object),
+        };
+        first = false;
+        write_export(&mut writer, &line)
+    })
+    .await?;
+    if format == JsonExportFormat::Array {
+        write_export(&mut writer, if first { "]\n" } else { "\n]\n" })?;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| AppError::Config(format!("Cannot write export file: {}", e)))?;
+    Ok(row_count)
+}
+
+/// Export a table's rows, optionally filtered, as INSERT statements.
+/// `where_clause` is trusted SQL typed by the user (without the WHERE keyword).
+#[tauri::command]
+pub async fn export_insert_sql(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    where_clause: Option<String>,
+) -> Result<String, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::export_insert_sql(&pool, &schema, &table, where_clause.as_deref()).await
+}
+
+/// Run several (label, sql) queries in order and write all results into one file.
+/// CSV/TSV get one block per query (label line, header, rows), Markdown gets a heading
+/// per query, and JSON is a single object keyed by label.
+/// With `stop_on_error` (the default) the export ends at the first failing query;
+/// otherwise the error is written in that query's section and the rest still run.
+#[tauri::command]
+pub async fn export_queries(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    queries: Vec<(String, String)>,
+    path: String,
+    format: ExportFormat,
+    stop_on_error: Option<bool>,
+) -> Result<Vec<ExportSectionResult>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    let stop_on_error = stop_on_error.unwrap_or(true);
+
+    let file = std::fs::File::create(&path)
+        .map_err(|e| AppError::Config(format!("Cannot create export file: {}", e)))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let mut json_sections = serde_json::Map::new();
+    let mut outcomes = Vec::with_capacity(queries.len());
+
+    for (label, sql) in queries {
+        let result = postgres::execute_query(&pool, &sql)
+            .await
+            .map_err(|e| e.to_string());
+
+        if format == ExportFormat::Json {
+            let value = match &result {
+                Ok(r) => JsonValue::Array(export::row_objects(r)),
+                Err(e) => serde_json::json!({ "error": e }),
+            };
+            // Repeated labels would overwrite each other in the object; number them.
+            let mut key = label.clone();
+            let mut n = 2;
+            while json_sections.contains_key(&key) {
+                key = format!("{} ({})", label, n);
+                n += 1;
+            }
+            json_sections.insert(key, value);
+        } else {
+            let section =
+                export::labeled_section(&label, result.as_ref().map_err(String::as_str), format);
+            write_export(&mut writer, &section)?;
+        }
+
+        let failed = result.is_err();
+        outcomes.push(ExportSectionResult {
+            label,
+            row_count: result.as_ref().map_or(0, |r| r.row_count),
+            error: result.err(),
+        });
+        if failed && stop_on_error {
+            break;
+        }
+    }
+
+    if format == ExportFormat::Json {
+        let json = serde_json::to_string_pretty(&json_sections)
+            .map_err(|e| AppError::Config(format!("JSON serialize error: {}", e)))?;
+        write_export(&mut writer, &json)?;
+    }
+    writer
+        .flush()
+        .map_err(|e| AppError::Config(format!("Cannot write export file: {}", e)))?;
+
+    Ok(outcomes)
+}
@@ -1,8 +1,13 @@
-use crate::models::{AppError, HistoryEntry, SavedQuery};
+use crate::commands::settings;
+use crate::db::script;
+use crate::models::{AppError, HistoryEntry, SavedQuery, SavedQuerySort};
+use crate::storage;
 
-const MAX_HISTORY: usize = 200;
+/// Serializes read-modify-write of history.json between concurrent commands (e.g. two
+/// windows adding entries at once), so one update can't overwrite the other.
+static HISTORY_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
-fn bestgres_dir() -> Result<std::path::PathBuf, AppError> {
+pub(crate) fn bestgres_dir() -> Result<std::path::PathBuf, AppError> {
     let dir = dirs::config_dir()
         .ok_or_else(|| AppError::Config("Cannot determine config directory".into()))?
         .join("bestgres");
@@ -28,47 +33,86 @@ fn queries_dir() -> Result<std::path::PathBuf, AppError> {
 
 // ── History ──
 
+/// Record an executed query. `error` is set for queries that failed; row count and
+/// timing are passed for those that succeeded.
 #[tauri::command]
-pub async fn add_to_history(sql: String, database: String) -> Result<(), AppError> {
+pub async fn add_to_history(
+    sql: String,
+    database: String,
+    connection_id: Option<String>,
+    row_count: Option<usize>,
+    execution_time_ms: Option<u64>,
+    error: Option<String>,
+) -> Result<(), AppError> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let path = history_path()?;
     let mut entries = load_history_entries(&path);
 
     let entry = HistoryEntry {
         sql,
         database,
+        connection_id,
         executed_at: chrono::Utc::now().to_rfc3339(),
+        row_count,
+        execution_time_ms,
+        success: error.is_none(),
+        error,
     };
 
-    // Prepend new entry
-    entries.insert(0, entry);
-
-    // Trim to max
-    entries.truncate(MAX_HISTORY);
-
-    let json = serde_json::to_string_pretty(&entries)
-        .map_err(|e| AppError::Config(format!("JSON serialize error: {}", e)))?;
-    std::fs::write(&path, json)
-        .map_err(|e| AppError::Config(format!("Cannot write history: {}", e)))?;
+    push_history_entry(&mut entries, entry, settings::load_settings().max_history);
+    write_history_entries(&path, &entries)
+}
 
-    Ok(())
+/// Put `entry` first and drop the oldest entries beyond `max_history` (0 = unlimited).
+fn push_history_entry(entries: &mut Vec<HistoryEntry>, entry: HistoryEntry, max_history: usize) {
+    entries.insert(0, entry);
+    if max_history > 0 {
+        entries.truncate(max_history);
+    }
 }
 
+/// History entries, newest first. With `connection_id`, only that connection's entries;
+/// otherwise all of them.
 #[tauri::command]
-pub async fn get_history() -> Result<Vec<HistoryEntry>, AppError> {
-    let path = history_path()?;
-    Ok(load_history_entries(&path))
+pub async fn get_history(connection_id: Option<String>) -> Result<Vec<HistoryEntry>, AppError> {
+    Ok(history_for(&history_path()?, connection_id.as_deref()))
 }
 
+/// Clear history for one connection, or all of it when `connection_id` is None.
 #[tauri::command]
-pub async fn clear_history() -> Result<(), AppError> {
-    let path = history_path()?;
+pub async fn clear_history(connection_id: Option<String>) -> Result<(), AppError> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    clear_history_file(&history_path()?, connection_id.as_deref())
+}
+
+fn history_for(path: &std::path::Path, connection_id: Option<&str>) -> Vec<HistoryEntry> {
+    let mut entries = load_history_entries(path);
+    if let Some(connection_id) = connection_id {
+        entries.retain(|e| e.connection_id.as_deref() == Some(connection_id));
+    }
+    entries
+}
+
+fn clear_history_file(path: &std::path::Path, connection_id: Option<&str>) -> Result<(), AppError> {
+    if let Some(connection_id) = connection_id {
+        let mut entries = load_history_entries(path);
+        entries.retain(|e| e.connection_id.as_deref() != Some(connection_id));
+        return write_history_entries(path, &entries);
+    }
     if path.exists() {
-        std::fs::remove_file(&path)
+        std::fs::remove_file(path)
             .map_err(|e| AppError::Config(format!("Cannot delete history: {}", e)))?;
     }
     Ok(())
 }
 
+fn write_history_entries(path: &std::path::Path, entries: &[HistoryEntry]) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(entries)
+        .map_err(|e| AppError::Config(format!("JSON serialize error: {}", e)))?;
+    storage::write_atomic(path, json.as_bytes())
+        .map_err(|e| AppError::Config(format!("Cannot write history: {}", e)))
+}
+
 fn load_history_entries(path: &std::path::Path) -> Vec<HistoryEntry> {
     if !path.exists() {
         return Vec::new();
@@ -81,30 +125,151 @@ fn load_history_entries(path: &std::path::Path) -> Vec<HistoryEntry> {
 
 // ── Saved queries ──
 
-#[tauri::command]
-pub async fn save_query(id: String, name: String, sql: String, database: String) -> Result<(), AppError> {
-    let dir = queries_dir()?;
-    let entry = SavedQuery { id: id.clone(), name, sql, database };
-
-    let safe_id: String = id.chars()
+fn sanitize_query_id(id: &str) -> String {
+    id.chars()
         .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
-        .collect();
-    let path = dir.join(format!("{}.json", safe_id));
+        .collect()
+}
+
+/// File for a saved query. Ids that are already filename-safe (uuids) are used as-is;
+/// anything else gets a hash of the raw id appended so that ids which sanitize to the
+/// same string (`a/b`, `a:b`) don't share a file.
+fn saved_query_path(dir: &std::path::Path, id: &str) -> std::path::PathBuf {
+    let safe_id = sanitize_query_id(id);
+    if safe_id == id && !id.is_empty() {
+        return dir.join(format!("{}.json", safe_id));
+    }
+    // FNV-1a: stable across builds, unlike std's DefaultHasher.
+    let hash = id.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    dir.join(format!("{}-{:08x}.json", safe_id, hash as u32))
+}
+
+/// Remove a file written under the old lossy naming scheme, but only if it
+/// actually holds the query with this id (it may belong to a colliding id).
+fn remove_legacy_query_file(dir: &std::path::Path, id: &str) {
+    let legacy = dir.join(format!("{}.json", sanitize_query_id(id)));
+    if legacy == saved_query_path(dir, id) {
+        return;
+    }
+    let holds_id = std::fs::read_to_string(&legacy)
+        .ok()
+        .and_then(|content| serde_json::from_str::<SavedQuery>(&content).ok())
+        .is_some_and(|q| q.id == id);
+    if holds_id {
+        let _ = std::fs::remove_file(&legacy);
+    }
+}
+
+/// Read a saved query by id, from its current file or one under the old naming scheme.
+fn read_saved_query(dir: &std::path::Path, id: &str) -> Option<SavedQuery> {
+    [
+        saved_query_path(dir, id),
+        dir.join(format!("{}.json", sanitize_query_id(id))),
+    ]
+    .iter()
+    .filter_map(|path| std::fs::read_to_string(path).ok())
+    .filter_map(|content| serde_json::from_str::<SavedQuery>(&content).ok())
+    .find(|q| q.id == id)
+}
 
-    let json = serde_json::to_string_pretty(&entry)
+fn write_saved_query(dir: &std::path::Path, query: &SavedQuery) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(query)
         .map_err(|e| AppError::Config(format!("JSON serialize error: {}", e)))?;
-    std::fs::write(&path, json)
+    storage::write_atomic(&saved_query_path(dir, &query.id), json.as_bytes())
         .map_err(|e| AppError::Config(format!("Cannot write saved query: {}", e)))?;
 
+    remove_legacy_query_file(dir, &query.id);
     Ok(())
 }
 
+/// Trim tags, dropping empty and repeated ones.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Save a query under `id`, replacing any previous version. `created_at` is kept from
+/// the first save; `updated_at` is set to now. Tags are kept when `tags` is None.
+/// With `format` the SQL is reformatted (see `format_sql`) before it is stored.
+#[tauri::command]
+pub async fn save_query(
+    id: String,
+    name: String,
+    sql: String,
+    database: String,
+    tags: Option<Vec<String>>,
+    format: Option<bool>,
+) -> Result<(), AppError> {
+    let sql = if format.unwrap_or(false) && !sql.trim().is_empty() {
+        script::format_sql(&sql)
+    } else {
+        sql
+    };
+    let dir = queries_dir()?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let existing = read_saved_query(&dir, &id);
+    let created_at = existing
+        .as_ref()
+        .and_then(|q| q.created_at.clone())
+        .unwrap_or_else(|| now.clone());
+    let tags = match tags {
+        Some(tags) => normalize_tags(tags),
+        None => existing.map(|q| q.tags).unwrap_or_default(),
+    };
+    let entry = SavedQuery {
+        id,
+        name,
+        sql,
+        database,
+        created_at: Some(created_at),
+        updated_at: Some(now),
+        tags,
+    };
+    write_saved_query(&dir, &entry)
+}
+
+/// Change some fields of an existing saved query, keeping its id and `created_at`.
 #[tauri::command]
-pub async fn list_saved_queries() -> Result<Vec<SavedQuery>, AppError> {
+pub async fn update_saved_query(
+    id: String,
+    name: Option<String>,
+    sql: Option<String>,
+    database: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<SavedQuery, AppError> {
     let dir = queries_dir()?;
+    let mut query = read_saved_query(&dir, &id)
+        .ok_or_else(|| AppError::Config(format!("Saved query not found: {}", id)))?;
+    if let Some(name) = name {
+        query.name = name;
+    }
+    if let Some(sql) = sql {
+        query.sql = sql;
+    }
+    if let Some(database) = database {
+        query.database = database;
+    }
+    if let Some(tags) = tags {
+        query.tags = normalize_tags(tags);
+    }
+    query.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    write_saved_query(&dir, &query)?;
+    Ok(query)
+}
+
+/// Read every saved query file in `dir`, migrating ones saved under the old naming scheme.
+fn load_saved_queries(dir: &std::path::Path) -> Result<Vec<SavedQuery>, AppError> {
     let mut queries = Vec::new();
 
-    let entries = std::fs::read_dir(&dir)
+    let entries = std::fs::read_dir(dir)
         .map_err(|e| AppError::Config(format!("Cannot read queries dir: {}", e)))?;
 
     for entry in entries {
@@ -113,28 +278,178 @@ pub async fn list_saved_queries() -> Result<Vec<SavedQuery>, AppError> {
         if path.extension().is_some_and(|ext| ext == "json") {
             if let Ok(content) = std::fs::read_to_string(&path) {
                 if let Ok(query) = serde_json::from_str::<SavedQuery>(&content) {
+                    // Migrate files saved under the old lossy naming scheme.
+                    let expected = saved_query_path(dir, &query.id);
+                    if path != expected && !expected.exists() {
+                        let _ = std::fs::rename(&path, &expected);
+                    }
                     queries.push(query);
                 }
             }
         }
     }
 
-    queries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
     Ok(queries)
 }
 
+/// All saved queries, by name unless `sort` says otherwise.
+#[tauri::command]
+pub async fn list_saved_queries(sort: Option<SavedQuerySort>) -> Result<Vec<SavedQuery>, AppError> {
+    let mut queries = load_saved_queries(&queries_dir()?)?;
+    match sort.unwrap_or(SavedQuerySort::Name) {
+        SavedQuerySort::Name => queries.sort_by_key(|q| q.name.to_lowercase()),
+        // RFC 3339 strings in UTC sort chronologically; None sorts before Some, so reversing
+        // puts untimed queries last.
+        SavedQuerySort::UpdatedAt => queries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+    }
+    Ok(queries)
+}
+
+/// Saved queries carrying `tag`, by name.
+#[tauri::command]
+pub async fn list_saved_queries_by_tag(tag: String) -> Result<Vec<SavedQuery>, AppError> {
+    let tag = tag.trim();
+    let mut queries = load_saved_queries(&queries_dir()?)?;
+    queries.retain(|q| q.tags.iter().any(|t| t == tag));
+    queries.sort_by_key(|q| q.name.to_lowercase());
+    Ok(queries)
+}
+
+/// Distinct tags across all saved queries, sorted.
+#[tauri::command]
+pub async fn list_tags() -> Result<Vec<String>, AppError> {
+    let mut tags: Vec<String> = load_saved_queries(&queries_dir()?)?
+        .into_iter()
+        .flat_map(|q| q.tags)
+        .collect();
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
 #[tauri::command]
 pub async fn delete_saved_query(id: String) -> Result<(), AppError> {
     let dir = queries_dir()?;
-    let safe_id: String = id.chars()
-        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
-        .collect();
-    let path = dir.join(format!("{}.json", safe_id));
+    let path = saved_query_path(&dir, &id);
 
     if path.exists() {
         std::fs::remove_file(&path)
             .map_err(|e| AppError::Config(format!("Cannot delete saved query: {}", e)))?;
     }
+    remove_legacy_query_file(&dir, &id);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A fresh, empty directory under the system temp dir.
+    fn temp_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("bestgres-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn history_entry(sql: &str, connection_id: &str) -> HistoryEntry {
+        serde_json::from_value(json!({
+            "sql": sql,
+            "database": "app",
+            "connection_id": connection_id,
+            "executed_at": "2026-01-01T00:00:00+00:00",
+            "success": true,
+        }))
+        .unwrap()
+    }
+
+    fn sqls(entries: &[HistoryEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.sql.as_str()).collect()
+    }
+
+    #[test]
+    fn history_keeps_the_newest_entries() {
+        let mut entries = Vec::new();
+        for sql in ["SELECT 1", "SELECT 2", "SELECT 3"] {
+            push_history_entry(&mut entries, history_entry(sql, "a"), 2);
+        }
+        assert_eq!(sqls(&entries), vec!["SELECT 3", "SELECT 2"]);
+
+        let mut unlimited = Vec::new();
+        for i in 0..600 {
+            let entry = history_entry(&format!("SELECT {i}"), "a");
+            push_history_entry(&mut unlimited, entry, 0);
+        }
+        assert_eq!(unlimited.len(), 600);
+        assert_eq!(unlimited[0].sql, "SELECT 599");
+    }
+
+    #[test]
+    fn history_is_fetched_and_cleared_per_connection() {
+        let dir = temp_dir();
+        let path = dir.join("history.json");
+        let mut entries = Vec::new();
+        for (sql, connection_id) in [("SELECT 1", "a"), ("SELECT 2", "b"), ("SELECT 3", "a")] {
+            push_history_entry(&mut entries, history_entry(sql, connection_id), 0);
+        }
+        write_history_entries(&path, &entries).unwrap();
+
+        assert_eq!(
+            sqls(&history_for(&path, Some("a"))),
+            vec!["SELECT 3", "SELECT 1"]
+        );
+        assert_eq!(sqls(&history_for(&path, Some("b"))), vec!["SELECT 2"]);
+        assert_eq!(history_for(&path, None).len(), 3);
+
+        clear_history_file(&path, Some("a")).unwrap();
+        assert!(history_for(&path, Some("a")).is_empty());
+        assert_eq!(sqls(&history_for(&path, None)), vec!["SELECT 2"]);
+
+        clear_history_file(&path, Some("b")).unwrap();
+        assert!(history_for(&path, None).is_empty());
+
+        write_history_entries(&path, &entries).unwrap();
+        clear_history_file(&path, None).unwrap();
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn saved_query(id: &str, name: &str) -> SavedQuery {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "sql": "SELECT 1",
+            "database": "app",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn colliding_ids_get_their_own_files() {
+        let dir = temp_dir();
+        write_saved_query(&dir, &saved_query("a/b", "slash")).unwrap();
+        write_saved_query(&dir, &saved_query("a:b", "colon")).unwrap();
+
+        assert_ne!(saved_query_path(&dir, "a/b"), saved_query_path(&dir, "a:b"));
+        assert_eq!(read_saved_query(&dir, "a/b").unwrap().name, "slash");
+        assert_eq!(read_saved_query(&dir, "a:b").unwrap().name, "colon");
+        assert_eq!(load_saved_queries(&dir).unwrap().len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn legacy_files_are_renamed_on_load() {
+        let dir = temp_dir();
+        let legacy = dir.join("a_b.json");
+        let query = saved_query("a/b", "slash");
+        std::fs::write(&legacy, serde_json::to_string(&query).unwrap()).unwrap();
+
+        let loaded = load_saved_queries(&dir).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, "a/b");
+        assert!(!legacy.exists());
+        assert!(saved_query_path(&dir, "a/b").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,9 @@
 pub mod connection;
+pub mod cursor;
+pub mod export;
 pub mod history;
+pub mod notify;
 pub mod query;
+pub mod session;
+pub mod settings;
+pub mod transaction;
@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+
+use sqlx::postgres::{PgListener, PgPoolOptions};
+use sqlx::PgPool;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::commands::connection::AppState;
+use crate::models::{AppError, PgNotification};
+
+/// Event emitted to the frontend for every notification received.
+pub const NOTIFICATION_EVENT: &str = "pg-notification";
+
+/// Handle to a connection's listener task. The task owns a `PgListener` on its own
+/// single-connection pool, so it never holds one of the query pool's connections.
+pub struct NotificationListener {
+    commands: mpsc::UnboundedSender<ListenerCommand>,
+}
+
+enum ListenerCommand {
+    Listen(String, oneshot::Sender<Result<(), AppError>>),
+    /// Replies with whether the listener is still subscribed to anything.
+    Unlisten(String, oneshot::Sender<Result<bool, AppError>>),
+}
+
+impl NotificationListener {
+    /// Open a dedicated connection with the same options as `pool` and start the task that
+    /// forwards notifications as `pg-notification` events.
+    async fn start(app: AppHandle, connection_id: String, pool: &PgPool) -> Result<Self, AppError> {
+        let listener_pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect_with((*pool.connect_options()).clone())
+            .await
+            .map_err(AppError::from_sqlx)?;
+        let listener = PgListener::connect_with(&listener_pool)
+            .await
+            .map_err(AppError::from_sqlx)?;
+
+        let (commands, receiver) = mpsc::unbounded_channel();
+        tauri::async_runtime::spawn(run_listener(app, connection_id, listener, receiver));
+        Ok(Self { commands })
+    }
+
+    async fn listen(&self, channel: &str) -> Result<(), AppError> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(ListenerCommand::Listen(channel.to_string(), reply))
+            .map_err(|_| listener_stopped())?;
+        response.await.map_err(|_| listener_stopped())?
+    }
+
+    async fn unlisten(&self, channel: &str) -> Result<bool, AppError> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(ListenerCommand::Unlisten(channel.to_string(), reply))
+            .map_err(|_| listener_stopped())?;
+        response.await.map_err(|_| listener_stopped())?
+    }
+
+    fn is_running(&self) -> bool {
+        !self.commands.is_closed()
+    }
+}
+
+fn listener_stopped() -> AppError {
+    AppError::Connection("Notification listener stopped".into())
+}
+
+/// Forward notifications until the handle is dropped, the last channel is unlistened, or
+/// the listener fails. Dropped connections are reconnected (and re-subscribed) by
+/// `PgListener` itself; notifications sent while disconnected are lost.
+async fn run_listener(
+    app: AppHandle,
+    connection_id: String,
+    mut listener: PgListener,
+    mut commands: mpsc::UnboundedReceiver<ListenerCommand>,
+) {
+    let mut channels: HashSet<String> = HashSet::new();
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(ListenerCommand::Listen(channel, reply)) => {
+                    let result = listener.listen(&channel).await.map_err(AppError::from_sqlx);
+                    if result.is_ok() {
+                        channels.insert(channel);
+                    }
+                    let _ = reply.send(result);
+                }
+                Some(ListenerCommand::Unlisten(channel, reply)) => {
+                    let result = listener.unlisten(&channel).await.map_err(AppError::from_sqlx);
+                    if result.is_ok() {
+                        channels.remove(&channel);
+                    }
+                    let active = !channels.is_empty();
+                    let _ = reply.send(result.map(|_| active));
+                    if !active {
+                        break;
+                    }
+                }
+                None => break,
+            },
+            notification = listener.recv() => match notification {
+                Ok(notification) => {
+                    let _ = app.emit(
+                        NOTIFICATION_EVENT,
+                        PgNotification {
+                            connection_id: connection_id.clone(),
+                            channel: notification.channel().to_string(),
+                            payload: notification.payload().to_string(),
+                            process_id: notification.process_id(),
+                        },
+                    );
+                }
+                Err(_) => break,
+            },
+        }
+    }
+}
+
+/// Stop a connection's listener, e.g. when its pools are closed.
+pub async fn stop_listener(state: &AppState, connection_id: &str) {
+    // Dropping the handle closes the command channel, which ends the task.
+    state.listeners.lock().await.remove(connection_id);
+}
+
+/// Subscribe to a NOTIFY channel on the connection's server. Notifications arrive as
+/// `pg-notification` events carrying a `PgNotification`.
+#[tauri::command]
+pub async fn listen(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+    channel: String,
+) -> Result<(), AppError> {
+    if channel.is_empty() {
+        return Err(AppError::database("Channel name must not be empty".into()));
+    }
+
+    let mut listeners = state.listeners.lock().await;
+    if !listeners
+        .get(&connection_id)
+        .is_some_and(|l| l.is_running())
+    {
+        let pool = state
+            .pools
+            .lock()
+            .await
+            .get(&connection_id)
+            .ok_or_else(|| AppError::Connection("Not connected".into()))?
+            .clone();
+        let listener = NotificationListener::start(app, connection_id.clone(), &pool).await?;
+        listeners.insert(connection_id.clone(), listener);
+    }
+    listeners[&connection_id].listen(&channel).await
+}
+
+/// Stop listening on a channel. The listener's connection is closed once no channels remain.
+#[tauri::command]
+pub async fn unlisten(
+    state: State<'_, AppState>,
+    connection_id: String,
+    channel: String,
+) -> Result<(), AppError> {
+    let mut listeners = state.listeners.lock().await;
+    let Some(listener) = listeners.get(&connection_id) else {
+        return Ok(());
+    };
+    let active = listener.is_running() && listener.unlisten(&channel).await?;
+    if !active {
+        listeners.remove(&connection_id);
+    }
+    Ok(())
+}
@@ -1,9 +1,18 @@
 use tauri::State;
 
-use crate::commands::connection::{get_or_create_db_pool, AppState};
-use crate::db::postgres;
-use crate::models::{AppError, ColumnInfo, QueryResult, SchemaObject, TableStructure};
+use crate::commands::connection::{
+    ensure_sql_stays_read_only, ensure_writable, get_or_create_db_pool, AppState,
+};
+use crate::commands::transaction::edit_connection;
+use crate::db::{ddl, diff, postgres, script};
+use crate::models::{
+    AppError, CellEdit, ColumnFilter, ColumnInfo, ColumnSource, CostEstimate, DatabaseSize,
+    EnumType, ExtensionInfo, LockInfo, MaintenanceOp, QueryResult, RoleInfo, SchemaDiff,
+    SchemaObject, SchemaObjectType, ScriptResult, SequenceInfo, ServerInfo, SessionInfo,
+    StatementOutcome, TableSize, TableStructure,
+};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 
 /// List all databases on the server for a connection.
 #[tauri::command]
@@ -21,15 +30,300 @@ pub async fn list_databases(
     postgres::list_databases(&pool).await
 }
 
-/// Get the schema tree (tables, views) for a specific database on a connection.
+/// List the sizes of the server's databases, largest first, for a size overview.
+#[tauri::command]
+pub async fn list_database_sizes(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<Vec<DatabaseSize>, AppError> {
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::list_database_sizes(&pool).await
+}
+
+/// Get the role the connection acts as, its attributes, and its role memberships.
+#[tauri::command]
+pub async fn get_current_role_info(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<RoleInfo, AppError> {
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::get_current_role_info(&pool).await
+}
+
+/// Get the server version and key settings for a connection.
+#[tauri::command]
+pub async fn get_server_info(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<ServerInfo, AppError> {
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::get_server_info(&pool).await
+}
+
+/// List sessions connected to the server, or only to the connection's database when
+/// `current_database_only` is set.
+#[tauri::command]
+pub async fn list_active_sessions(
+    state: State<'_, AppState>,
+    connection_id: String,
+    current_database_only: Option<bool>,
+) -> Result<Vec<SessionInfo>, AppError> {
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::list_active_sessions(&pool, current_database_only.unwrap_or(false)).await
+}
+
+/// List sessions blocked on locks together with the sessions blocking them.
+#[tauri::command]
+pub async fn list_blocking_locks(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> Result<Vec<LockInfo>, AppError> {
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::list_blocking_locks(&pool).await
+}
+
+/// Terminate a server session by pid. Returns false if it no longer existed.
+/// Refused on read-only connections.
+#[tauri::command]
+pub async fn terminate_session(
+    state: State<'_, AppState>,
+    connection_id: String,
+    pid: i32,
+) -> Result<bool, AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::signal_backend(&pool, pid, true).await
+}
+
+/// Terminate all other sessions on a database of the server, e.g. before dropping or
+/// renaming it. Returns the number terminated. Refused on read-only connections.
+#[tauri::command]
+pub async fn terminate_database_connections(
+    state: State<'_, AppState>,
+    connection_id: String,
+    target_database: String,
+) -> Result<u64, AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::terminate_database_connections(&pool, &target_database).await
+}
+
+/// Cancel a backend's running query, or terminate the backend when `terminate` is set.
+/// Returns whether the signal was delivered (false if the pid is not a backend).
+/// Refused on read-only connections.
+#[tauri::command]
+pub async fn cancel_backend(
+    state: State<'_, AppState>,
+    connection_id: String,
+    pid: i32,
+    terminate: bool,
+) -> Result<bool, AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pools = state.pools.lock().await;
+    let pool = pools
+        .get(&connection_id)
+        .ok_or_else(|| AppError::Connection("Not connected".into()))?
+        .clone();
+    drop(pools);
+
+    postgres::signal_backend(&pool, pid, terminate).await
+}
+
+/// Get the schema tree (tables, views, matviews, functions) for a database on a connection.
 #[tauri::command]
 pub async fn get_schema(
     state: State<'_, AppState>,
     connection_id: String,
     database: String,
+    schema_filter: Option<String>,
+) -> Result<Vec<SchemaObject>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::get_schema_objects(&pool, schema_filter.as_deref()).await
+}
+
+/// Search schema objects by name (case-insensitive substring), optionally only some kinds.
+/// Returns at most 500 matches.
+#[tauri::command]
+pub async fn search_schema_objects(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    pattern: String,
+    kinds: Vec<SchemaObjectType>,
 ) -> Result<Vec<SchemaObject>, AppError> {
     let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
-    postgres::get_schema_objects(&pool).await
+    postgres::search_schema_objects(&pool, &pattern, &kinds).await
+}
+
+/// List the non-system schemas in a database.
+#[tauri::command]
+pub async fn list_schemas(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+) -> Result<Vec<String>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::list_schemas(&pool).await
+}
+
+/// Get the SQL definition of a view or function.
+#[tauri::command]
+pub async fn get_object_definition(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    name: String,
+    object_type: SchemaObjectType,
+) -> Result<String, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::get_object_definition(&pool, &schema, &name, &object_type).await
+}
+
+/// List enum types in a database with their allowed values.
+#[tauri::command]
+pub async fn list_enum_types(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+) -> Result<Vec<EnumType>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::list_enum_types(&pool).await
+}
+
+/// List the extensions installed in a database, with their versions and schemas.
+#[tauri::command]
+pub async fn list_extensions(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+) -> Result<Vec<ExtensionInfo>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::list_extensions(&pool).await
+}
+
+/// Install an extension in a database if it isn't installed yet.
+#[tauri::command]
+pub async fn create_extension(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    name: String,
+) -> Result<(), AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::create_extension(&pool, &name).await
+}
+
+/// Get a sequence's current value, increment, bounds, and cycle flag.
+#[tauri::command]
+pub async fn get_sequence_info(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    name: String,
+) -> Result<SequenceInfo, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::get_sequence_info(&pool, &schema, &name).await
+}
+
+/// Estimated number of rows in a table (from planner statistics, not a COUNT).
+#[tauri::command]
+pub async fn get_table_row_estimate(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+) -> Result<i64, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::get_table_row_estimate(&pool, &schema, &table).await
+}
+
+/// Size of a table, its data, and its indexes in bytes.
+#[tauri::command]
+pub async fn get_table_size(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+) -> Result<TableSize, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::get_table_size(&pool, &schema, &table).await
+}
+
+/// Refresh a materialized view.
+#[tauri::command]
+pub async fn refresh_materialized_view(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    name: String,
+) -> Result<(), AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::refresh_materialized_view(&pool, &schema, &name).await
+}
+
+/// Vacuum, analyze or reindex a table. Lock waits and permission errors are returned
+/// as database errors.
+#[tauri::command]
+pub async fn maintain_table(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    op: MaintenanceOp,
+) -> Result<(), AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::maintain_table(&pool, &schema, &table, op).await
 }
 
 /// Get primary key column names for a table, in constraint order.
@@ -59,6 +353,18 @@ pub async fn get_columns(
     postgres::get_columns(&pool, &schema, &table).await
 }
 
+/// Get column info for many (schema, table) pairs in one round trip, keyed by "schema.table".
+#[tauri::command]
+pub async fn get_columns_bulk(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    tables: Vec<(String, String)>,
+) -> Result<HashMap<String, Vec<ColumnInfo>>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::get_columns_bulk(&pool, &tables).await
+}
+
 /// Get the full structure (DDL info) for a table.
 #[tauri::command]
 pub async fn get_table_structure(
@@ -72,19 +378,201 @@ pub async fn get_table_structure(
     postgres::get_table_structure(&pool, &schema, &table).await
 }
 
+/// Compare the structures of two tables, possibly on different connections or databases.
+/// "Added" items exist only in table b, "removed" ones only in table a.
+#[tauri::command]
+pub async fn diff_table_structure(
+    state: State<'_, AppState>,
+    connection_a: String,
+    db_a: String,
+    schema_a: String,
+    table_a: String,
+    connection_b: String,
+    db_b: String,
+    schema_b: String,
+    table_b: String,
+) -> Result<SchemaDiff, AppError> {
+    let pool_a = get_or_create_db_pool(&state, &connection_a, &db_a).await?;
+    let pool_b = get_or_create_db_pool(&state, &connection_b, &db_b).await?;
+    let (structure_a, structure_b) = tokio::try_join!(
+        postgres::get_table_structure(&pool_a, &schema_a, &table_a),
+        postgres::get_table_structure(&pool_b, &schema_b, &table_b),
+    )?;
+    Ok(diff::diff_structures(&structure_a, &structure_b))
+}
+
+/// Get a runnable CREATE TABLE script (with constraints, indexes and comments) for a table.
+#[tauri::command]
+pub async fn get_table_ddl(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+) -> Result<String, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    let structure = postgres::get_table_structure(&pool, &schema, &table).await?;
+    Ok(ddl::table_ddl(&schema, &table, &structure))
+}
+
+/// Reformat SQL with upper-case keywords and consistent indentation.
+#[tauri::command]
+pub async fn format_sql(sql: String) -> Result<String, AppError> {
+    if sql.trim().is_empty() {
+        return Err(AppError::database("Nothing to format".into()));
+    }
+    Ok(script::format_sql(&sql))
+}
+
 /// Execute a SQL query against a specific database on a connection.
+/// With `timeout_ms` set (and non-zero) the query is cancelled once it runs that long.
+/// With `select_only` anything but a SELECT is refused before it reaches the server.
+/// With `max_rows` a single SELECT returns at most that many rows, and `truncated` is set
+/// if there were more.
 #[tauri::command]
 pub async fn execute_query(
     state: State<'_, AppState>,
     connection_id: String,
     database: String,
     sql: String,
+    timeout_ms: Option<u64>,
+    select_only: Option<bool>,
+    max_rows: Option<usize>,
+) -> Result<QueryResult, AppError> {
+    if select_only.unwrap_or(false) {
+        postgres::ensure_select_only(&sql)?;
+    }
+    ensure_sql_stays_read_only(&state, &connection_id, &sql).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    let timeout_ms = timeout_ms.filter(|ms| *ms > 0);
+    let mut result = match (max_rows, timeout_ms) {
+        (Some(max_rows), _) => {
+            postgres::execute_query_capped(&pool, &sql, max_rows, timeout_ms).await?
+        }
+        (None, Some(ms)) => postgres::execute_query_with_timeout(&pool, &sql, ms).await?,
+        (None, None) => postgres::execute_query(&pool, &sql).await?,
+    };
+    // The rows are what matter; a failed lookup only means no inline editing.
+    if let Err(e) = postgres::mark_editable(&pool, &sql, &mut result).await {
+        eprintln!("Could not check whether the result is editable: {}", e);
+    }
+    Ok(result)
+}
+
+/// Execute a query with bound parameters for its `$1..$n` placeholders, so values never
+/// have to be spliced into the SQL text.
+#[tauri::command]
+pub async fn execute_query_params(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+    params: Vec<JsonValue>,
 ) -> Result<QueryResult, AppError> {
+    ensure_sql_stays_read_only(&state, &connection_id, &sql).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::execute_query_params(&pool, &sql, &params).await
+}
+
+/// Get the JSON plan for a query; with `analyze` it is executed and rolled back.
+#[tauri::command]
+pub async fn explain_query(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+    analyze: bool,
+) -> Result<String, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::explain_query(&pool, &sql, analyze).await
+}
+
+/// Get the planner's total cost and row estimate for a query without running it.
+#[tauri::command]
+pub async fn estimate_query_cost(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+) -> Result<CostEstimate, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::estimate_query_cost(&pool, &sql).await
+}
+
+/// Execute a multi-statement script, returning one result per statement.
+#[tauri::command]
+pub async fn execute_script(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+) -> Result<ScriptResult, AppError> {
+    ensure_sql_stays_read_only(&state, &connection_id, &sql).await?;
     let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
-    postgres::execute_query(&pool, &sql).await
+    postgres::execute_script(&pool, &sql).await
+}
+
+/// Run a script in a transaction that is always rolled back, to check it would succeed.
+#[tauri::command]
+pub async fn dry_run_script(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+) -> Result<Vec<StatementOutcome>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::dry_run_script(&pool, &sql).await
+}
+
+/// Execute a query one page at a time (LIMIT/OFFSET applied around a single SELECT).
+#[tauri::command]
+pub async fn execute_query_paged(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+    limit: i64,
+    offset: i64,
+) -> Result<QueryResult, AppError> {
+    ensure_sql_stays_read_only(&state, &connection_id, &sql).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::execute_query_paged(&pool, &sql, limit, offset).await
+}
+
+/// Fetch a page of a table's rows for the grid, with structured filters and a sort of
+/// (column, ascending) pairs, without the caller writing SQL.
+#[tauri::command]
+pub async fn browse_table(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    order_by: Vec<(String, bool)>,
+    filters: Vec<ColumnFilter>,
+    limit: i64,
+    offset: i64,
+) -> Result<QueryResult, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::browse_table(&pool, &schema, &table, &order_by, &filters, limit, offset).await
+}
+
+/// Map each result column of a query to its source table column, if it has one.
+/// Lets the grid offer editing on joined results for columns backed by a base table.
+#[tauri::command]
+pub async fn describe_query_sources(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    sql: String,
+) -> Result<Vec<ColumnSource>, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::describe_query_sources(&pool, &sql).await
 }
 
 /// Update a single cell value in a table. Requires a primary key to identify the row.
+/// Edits run inside the transaction `tx_id` when given, and autocommit otherwise.
+/// `set_null` writes SQL NULL regardless of `new_value`, so NULL and '' stay distinct.
 #[tauri::command]
 pub async fn update_cell(
     state: State<'_, AppState>,
@@ -96,21 +584,72 @@ pub async fn update_cell(
     primary_key_columns: Vec<String>,
     primary_key_values: Vec<JsonValue>,
     new_value: JsonValue,
+    set_null: Option<bool>,
+    tx_id: Option<String>,
 ) -> Result<u64, AppError> {
-    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    let mut conn = edit_connection(&state, &connection_id, &database, tx_id).await?;
+    let new_value = if set_null.unwrap_or(false) {
+        None
+    } else {
+        Some(&new_value)
+    };
     postgres::update_cell(
-        &pool,
+        conn.conn(),
         &schema,
         &table,
         &column,
         &primary_key_columns,
         &primary_key_values,
+        new_value,
+    )
+    .await
+}
+
+/// Apply several cell edits to a table in one transaction; all succeed or none do.
+#[tauri::command]
+pub async fn update_cells(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    edits: Vec<CellEdit>,
+    tx_id: Option<String>,
+) -> Result<u64, AppError> {
+    let mut conn = edit_connection(&state, &connection_id, &database, tx_id).await?;
+    postgres::update_cells(conn.conn(), &schema, &table, &edits).await
+}
+
+/// Set a column to a value on all rows matching the filters.
+/// Requires at least one filter unless `allow_all` is true.
+#[tauri::command]
+pub async fn bulk_update(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    column: String,
+    new_value: JsonValue,
+    filters: Vec<ColumnFilter>,
+    allow_all: Option<bool>,
+    tx_id: Option<String>,
+) -> Result<u64, AppError> {
+    let mut conn = edit_connection(&state, &connection_id, &database, tx_id).await?;
+    postgres::bulk_update(
+        conn.conn(),
+        &schema,
+        &table,
+        &column,
         &new_value,
+        &filters,
+        allow_all.unwrap_or(false),
     )
     .await
 }
 
-/// Insert a new row into a table.
+/// Insert a new row into a table and return it as stored.
+/// Columns left out of `values` get their default; a null value inserts NULL.
 #[tauri::command]
 pub async fn insert_row(
     state: State<'_, AppState>,
@@ -118,32 +657,66 @@ pub async fn insert_row(
     database: String,
     schema: String,
     table: String,
-    columns: Vec<String>,
-    values: Vec<JsonValue>,
-    column_types: Vec<String>,
-) -> Result<u64, AppError> {
-    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
-    postgres::insert_row(&pool, &schema, &table, &columns, &values, &column_types).await
+    values: HashMap<String, Option<JsonValue>>,
+    tx_id: Option<String>,
+) -> Result<QueryResult, AppError> {
+    let mut conn = edit_connection(&state, &connection_id, &database, tx_id).await?;
+    postgres::insert_row(conn.conn(), &schema, &table, &values).await
 }
 
-/// Delete rows by primary key. Each inner vec is one row's PK values.
+/// Insert a copy of a row, identified by its primary key, and return the new row.
+/// Generated and auto-increment columns get fresh values.
 #[tauri::command]
-pub async fn delete_rows(
+pub async fn duplicate_row(
     state: State<'_, AppState>,
     connection_id: String,
     database: String,
     schema: String,
     table: String,
     primary_key_columns: Vec<String>,
-    primary_key_values_list: Vec<Vec<JsonValue>>,
-) -> Result<u64, AppError> {
-    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
-    postgres::delete_rows(
-        &pool,
+    primary_key_values: Vec<JsonValue>,
+    tx_id: Option<String>,
+) -> Result<QueryResult, AppError> {
+    let mut conn = edit_connection(&state, &connection_id, &database, tx_id).await?;
+    postgres::duplicate_row(
+        conn.conn(),
         &schema,
         &table,
         &primary_key_columns,
-        &primary_key_values_list,
+        &primary_key_values,
     )
     .await
 }
+
+/// Import a CSV file into an existing table, returning the number of rows inserted.
+/// With `has_header`, columns are matched by name; otherwise by position.
+#[tauri::command]
+pub async fn import_csv(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    path: String,
+    has_header: bool,
+) -> Result<u64, AppError> {
+    ensure_writable(&state, &connection_id).await?;
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    postgres::import_csv(&pool, &schema, &table, &path, has_header).await
+}
+
+/// Delete rows by primary key. Each inner vec is one row's PK values.
+#[tauri::command]
+pub async fn delete_rows(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+    schema: String,
+    table: String,
+    primary_key_columns: Vec<String>,
+    rows: Vec<Vec<JsonValue>>,
+    tx_id: Option<String>,
+) -> Result<u64, AppError> {
+    let mut conn = edit_connection(&state, &connection_id, &database, tx_id).await?;
+    postgres::delete_rows(conn.conn(), &schema, &table, &primary_key_columns, &rows).await
+}
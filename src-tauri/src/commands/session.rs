@@ -0,0 +1,31 @@
+use crate::commands::history::bestgres_dir;
+use crate::models::{AppError, TabState};
+use crate::storage;
+
+fn session_path() -> Result<std::path::PathBuf, AppError> {
+    Ok(bestgres_dir()?.join("session.json"))
+}
+
+/// Save the open query tabs, replacing the previously saved ones.
+#[tauri::command]
+pub async fn save_session(tabs: Vec<TabState>) -> Result<(), AppError> {
+    let path = session_path()?;
+    let json = serde_json::to_string_pretty(&tabs)
+        .map_err(|e| AppError::Config(format!("JSON serialize error: {}", e)))?;
+    storage::write_atomic(&path, json.as_bytes())
+        .map_err(|e| AppError::Config(format!("Cannot write session: {}", e)))?;
+    Ok(())
+}
+
+/// The tabs saved by `save_session`; none if nothing was saved yet.
+#[tauri::command]
+pub async fn load_session() -> Result<Vec<TabState>, AppError> {
+    let path = session_path()?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::Config(format!("Cannot read session: {}", e))),
+    };
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Config(format!("Invalid session file: {}", e)))
+}
@@ -0,0 +1,31 @@
+use crate::commands::history::bestgres_dir;
+use crate::models::{AppError, Settings};
+use crate::storage;
+
+fn settings_path() -> Result<std::path::PathBuf, AppError> {
+    Ok(bestgres_dir()?.join("settings.json"))
+}
+
+/// Current settings. A missing or unreadable file gives the defaults.
+pub fn load_settings() -> Settings {
+    settings_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_settings() -> Result<Settings, AppError> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn update_settings(settings: Settings) -> Result<(), AppError> {
+    let path = settings_path()?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| AppError::Config(format!("JSON serialize error: {}", e)))?;
+    storage::write_atomic(&path, json.as_bytes())
+        .map_err(|e| AppError::Config(format!("Cannot write settings: {}", e)))?;
+    Ok(())
+}
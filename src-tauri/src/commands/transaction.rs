@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgConnection;
+use sqlx::{Postgres, Transaction};
+use tauri::State;
+use tokio::sync::MutexGuard;
+
+use crate::commands::connection::{ensure_writable, get_or_create_db_pool, AppState};
+use crate::db::postgres::{is_valid_identifier, quote_ident};
+use crate::models::AppError;
+
+/// A transaction opened by `begin_transaction`, held until it is committed or rolled back.
+pub struct OpenTransaction {
+    pub connection_id: String,
+    pub tx: Transaction<'static, Postgres>,
+    /// Active savepoints, oldest first. Names may repeat; the latest one is the one
+    /// ROLLBACK TO and RELEASE refer to, as in Postgres.
+    pub savepoints: Vec<String>,
+}
+
+/// The connection an edit runs on: an open transaction's, or one taken from the pool
+/// (which autocommits as before).
+pub enum EditConnection<'a> {
+    Transaction(MutexGuard<'a, HashMap<String, OpenTransaction>>, String),
+    Pool(PoolConnection<Postgres>),
+}
+
+impl EditConnection<'_> {
+    pub fn conn(&mut self) -> &mut PgConnection {
+        match self {
+            // The id was looked up when the guard was taken and can't have been removed since.
+            EditConnection::Transaction(transactions, tx_id) => {
+                &mut transactions.get_mut(tx_id.as_str()).unwrap().tx
+            }
+            EditConnection::Pool(conn) => conn,
+        }
+    }
+}
+
+/// Pick the connection for an edit: the transaction `tx_id` when given, otherwise a
+/// pooled connection for the connection/database pair. Read-only connections are refused.
+pub async fn edit_connection<'a>(
+    state: &'a AppState,
+    connection_id: &str,
+    database: &str,
+    tx_id: Option<String>,
+) -> Result<EditConnection<'a>, AppError> {
+    ensure_writable(state, connection_id).await?;
+    match tx_id {
+        Some(tx_id) => {
+            let transactions = state.transactions.lock().await;
+            if !transactions.contains_key(&tx_id) {
+                return Err(AppError::database(format!(
+                    "Transaction {} not found",
+                    tx_id
+                )));
+            }
+            Ok(EditConnection::Transaction(transactions, tx_id))
+        }
+        None => {
+            let pool = get_or_create_db_pool(state, connection_id, database).await?;
+            let conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+            Ok(EditConnection::Pool(conn))
+        }
+    }
+}
+
+/// Roll back every open transaction on a connection, e.g. before its pools are closed
+/// (closing a pool waits for connections held by transactions to be returned).
+pub async fn discard_transactions(state: &AppState, connection_id: &str) {
+    let mut transactions = state.transactions.lock().await;
+    let ids: Vec<String> = transactions
+        .iter()
+        .filter(|(_, open)| open.connection_id == connection_id)
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in ids {
+        if let Some(open) = transactions.remove(&id) {
+            let _ = open.tx.rollback().await;
+        }
+    }
+}
+
+/// Begin a transaction on a database. Edits passing the returned id run inside it
+/// until `commit_transaction` or `rollback_transaction` is called.
+#[tauri::command]
+pub async fn begin_transaction(
+    state: State<'_, AppState>,
+    connection_id: String,
+    database: String,
+) -> Result<String, AppError> {
+    let pool = get_or_create_db_pool(&state, &connection_id, &database).await?;
+    let tx = pool.begin().await.map_err(AppError::from_sqlx)?;
+
+    let tx_id = uuid::Uuid::new_v4().to_string();
+    state.transactions.lock().await.insert(
+        tx_id.clone(),
+        OpenTransaction {
+            connection_id,
+            tx,
+            savepoints: Vec::new(),
+        },
+    );
+    Ok(tx_id)
+}
+
+/// Commit an open transaction.
+#[tauri::command]
+pub async fn commit_transaction(state: State<'_, AppState>, tx_id: String) -> Result<(), AppError> {
+    let open = take_transaction(&state, &tx_id).await?;
+    open.tx.commit().await.map_err(AppError::from_sqlx)
+}
+
+/// Roll back an open transaction, discarding its edits.
+#[tauri::command]
+pub async fn rollback_transaction(
+    state: State<'_, AppState>,
+    tx_id: String,
+) -> Result<(), AppError> {
+    let open = take_transaction(&state, &tx_id).await?;
+    open.tx.rollback().await.map_err(AppError::from_sqlx)
+}
+
+async fn take_transaction(state: &AppState, tx_id: &str) -> Result<OpenTransaction, AppError> {
+    state
+        .transactions
+        .lock()
+        .await
+        .remove(tx_id)
+        .ok_or_else(|| AppError::database(format!("Transaction {} not found", tx_id)))
+}
+
+/// Set a savepoint in an open transaction. Edits after it can be undone with
+/// `rollback_to_savepoint` without rolling back the whole transaction.
+#[tauri::command]
+pub async fn savepoint(
+    state: State<'_, AppState>,
+    tx_id: String,
+    name: String,
+) -> Result<(), AppError> {
+    if !is_valid_identifier(&name) {
+        return Err(AppError::database("Invalid savepoint name".into()));
+    }
+    let mut transactions = state.transactions.lock().await;
+    let open = open_transaction(&mut transactions, &tx_id)?;
+    sqlx::raw_sql(&format!("SAVEPOINT {}", quote_ident(&name)))
+        .execute(&mut *open.tx)
+        .await
+        .map_err(AppError::from_sqlx)?;
+    open.savepoints.push(name);
+    Ok(())
+}
+
+/// Undo everything done in a transaction since a savepoint. The savepoint stays active;
+/// savepoints set after it are discarded.
+#[tauri::command]
+pub async fn rollback_to_savepoint(
+    state: State<'_, AppState>,
+    tx_id: String,
+    name: String,
+) -> Result<(), AppError> {
+    let mut transactions = state.transactions.lock().await;
+    let open = open_transaction(&mut transactions, &tx_id)?;
+    let index = savepoint_index(&open.savepoints, &name)?;
+    sqlx::raw_sql(&format!("ROLLBACK TO SAVEPOINT {}", quote_ident(&name)))
+        .execute(&mut *open.tx)
+        .await
+        .map_err(AppError::from_sqlx)?;
+    open.savepoints.truncate(index + 1);
+    Ok(())
+}
+
+/// Forget a savepoint, keeping its edits. Savepoints set after it are released too.
+#[tauri::command]
+pub async fn release_savepoint(
+    state: State<'_, AppState>,
+    tx_id: String,
+    name: String,
+) -> Result<(), AppError> {
+    let mut transactions = state.transactions.lock().await;
+    let open = open_transaction(&mut transactions, &tx_id)?;
+    let index = savepoint_index(&open.savepoints, &name)?;
+    sqlx::raw_sql(&format!("RELEASE SAVEPOINT {}", quote_ident(&name)))
+        .execute(&mut *open.tx)
+        .await
+        .map_err(AppError::from_sqlx)?;
+    open.savepoints.truncate(index);
+    Ok(())
+}
+
+/// Active savepoints of an open transaction, oldest first.
+#[tauri::command]
+pub async fn list_savepoints(
+    state: State<'_, AppState>,
+    tx_id: String,
+) -> Result<Vec<String>, AppError> {
+    let mut transactions = state.transactions.lock().await;
+    Ok(open_transaction(&mut transactions, &tx_id)?
+        .savepoints
+        .clone())
+}
+
+fn open_transaction<'a>(
+    transactions: &'a mut HashMap<String, OpenTransaction>,
+    tx_id: &str,
+) -> Result<&'a mut OpenTransaction, AppError> {
+    transactions
+        .get_mut(tx_id)
+        .ok_or_else(|| AppError::database(format!("Transaction {} not found", tx_id)))
+}
+
+/// Position of the latest savepoint called `name`. Unknown names are refused here rather
+/// than by the server, where the error would abort the transaction.
+fn savepoint_index(savepoints: &[String], name: &str) -> Result<usize, AppError> {
+    savepoints
+        .iter()
+        .rposition(|s| s == name)
+        .ok_or_else(|| AppError::database(format!("Savepoint {} not found", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn savepoint_index_finds_latest_of_repeated_name() {
+        let savepoints = names(&["a", "b", "a", "c"]);
+        assert_eq!(savepoint_index(&savepoints, "a").unwrap(), 2);
+        assert_eq!(savepoint_index(&savepoints, "b").unwrap(), 1);
+    }
+
+    #[test]
+    fn savepoint_index_rejects_unknown_name() {
+        let savepoints = names(&["a"]);
+        assert!(savepoint_index(&savepoints, "b").is_err());
+        assert!(savepoint_index(&[], "a").is_err());
+    }
+
+    #[test]
+    fn savepoint_names_are_quoted() {
+        assert!(!is_valid_identifier(""));
+        assert!(!is_valid_identifier("a\0b"));
+        assert_eq!(
+            format!("SAVEPOINT {}", quote_ident("before \"bulk\" edit")),
+            "SAVEPOINT \"before \"\"bulk\"\" edit\""
+        );
+    }
+}
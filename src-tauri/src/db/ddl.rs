@@ -0,0 +1,260 @@
+use crate::db::export::sql_literal;
+use crate::db::postgres::quote_ident;
+use crate::models::{ColumnDetail, TableStructure};
+
+/// Reconstruct a runnable DDL script for a table from its structure: CREATE TABLE, then
+/// the primary key, CHECK/UNIQUE/EXCLUDE constraints, foreign keys, remaining indexes
+/// and comments. Integer columns defaulting to `nextval(...)` become serial columns, so
+/// the script doesn't depend on a sequence existing. Triggers are left out, since
+/// their functions are not part of the table.
+pub fn table_ddl(schema: &str, table: &str, structure: &TableStructure) -> String {
+    let qualified = format!("{}.{}", quote_ident(schema), quote_ident(table));
+    let mut out = format!("CREATE TABLE {} (\n", qualified);
+    let column_lines: Vec<String> = structure
+        .columns
+        .iter()
+        .map(|col| format!("    {}", column_definition(col)))
+        .collect();
+    out.push_str(&column_lines.join(",\n"));
+    out.push_str("\n);\n");
+
+    let mut statements = Vec::new();
+    for index in structure.indexes.iter().filter(|i| i.is_primary) {
+        if let Some(key) = index_key(&index.definition) {
+            statements.push(format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} PRIMARY KEY {};",
+                qualified,
+                quote_ident(&index.name),
+                key
+            ));
+        }
+    }
+    for constraint in &structure.constraints {
+        statements.push(format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} {};",
+            qualified,
+            quote_ident(&constraint.name),
+            constraint.definition
+        ));
+    }
+
+    // Foreign keys arrive as one row per column; group them by constraint name.
+    let mut foreign_keys: Vec<(&str, Vec<&str>, String, Vec<&str>)> = Vec::new();
+    for fk in &structure.foreign_keys {
+        if foreign_keys.last().map(|(name, ..)| *name) != Some(fk.name.as_str()) {
+            foreign_keys.push((
+                fk.name.as_str(),
+                Vec::new(),
+                format!(
+                    "{}.{}",
+                    quote_ident(&fk.ref_schema),
+                    quote_ident(&fk.ref_table)
+                ),
+                Vec::new(),
+            ));
+        }
+        let (_, columns, _, ref_columns) = foreign_keys.last_mut().unwrap();
+        if !columns.contains(&fk.column_name.as_str()) {
+            columns.push(fk.column_name.as_str());
+        }
+        if !ref_columns.contains(&fk.ref_column.as_str()) {
+            ref_columns.push(fk.ref_column.as_str());
+        }
+    }
+    for (name, columns, ref_table, ref_columns) in foreign_keys {
+        statements.push(format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({});",
+            qualified,
+            quote_ident(name),
+            quote_list(&columns),
+            ref_table,
+            quote_list(&ref_columns)
+        ));
+    }
+
+    // Indexes backing the primary key or a constraint are created by ADD CONSTRAINT.
+    for index in &structure.indexes {
+        let backs_constraint =
+            index.is_primary || structure.constraints.iter().any(|c| c.name == index.name);
+        if !backs_constraint {
+            statements.push(format!("{};", index.definition));
+        }
+    }
+
+    if let Some(comment) = &structure.table_comment {
+        statements.push(format!(
+            "COMMENT ON TABLE {} IS {};",
+            qualified,
+            sql_literal(Some(comment), "text")
+        ));
+    }
+    for col in &structure.columns {
+        if let Some(comment) = &col.comment {
+            statements.push(format!(
+                "COMMENT ON COLUMN {}.{} IS {};",
+                qualified,
+                quote_ident(&col.name),
+                sql_literal(Some(comment), "text")
+            ));
+        }
+    }
+
+    if !statements.is_empty() {
+        out.push('\n');
+        out.push_str(&statements.join("\n"));
+        out.push('\n');
+    }
+    out
+}
+
+/// One column line of CREATE TABLE: name, type, and identity/generated/default/NOT NULL.
+fn column_definition(col: &ColumnDetail) -> String {
+    let serial_type = match col.data_type.as_str() {
+        "smallint" => Some("smallserial"),
+        "integer" => Some("serial"),
+        "bigint" => Some("bigserial"),
+        _ => None,
+    }
+    .filter(|_| {
+        col.raw_default
+            .as_deref()
+            .is_some_and(|d| d.starts_with("nextval("))
+    });
+
+    let mut def = format!(
+        "{} {}",
+        quote_ident(&col.name),
+        serial_type.unwrap_or(col.data_type.as_str())
+    );
+    if let Some(generation) = &col.identity_generation {
+        def.push_str(&format!(" GENERATED {} AS IDENTITY", generation));
+    } else if let Some(expression) = &col.generation_expression {
+        def.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expression));
+    } else if let (None, Some(default)) = (serial_type, &col.raw_default) {
+        def.push_str(&format!(" DEFAULT {}", default));
+    }
+    if !col.is_nullable {
+        def.push_str(" NOT NULL");
+    }
+    def
+}
+
+/// The key part of a pg_get_indexdef definition, e.g. `(id) INCLUDE (name)` from
+/// `CREATE UNIQUE INDEX t_pkey ON public.t USING btree (id) INCLUDE (name)`.
+fn index_key(definition: &str) -> Option<&str> {
+    let (_, rest) = definition.split_once(" USING ")?;
+    let (_method, key) = rest.split_once(' ')?;
+    Some(key)
+}
+
+fn quote_list(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| quote_ident(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column(name: &str, data_type: &str, fields: serde_json::Value) -> serde_json::Value {
+        let mut column = json!({
+            "name": name,
+            "data_type": data_type,
+            "is_nullable": false,
+            "default_value": null,
+        });
+        for (key, field) in fields.as_object().unwrap() {
+            column[key] = field.clone();
+        }
+        column
+    }
+
+    fn orders() -> TableStructure {
+        serde_json::from_value(json!({
+            "columns": [
+                column("id", "integer", json!({
+                    "raw_default": "nextval('orders_id_seq'::regclass)",
+                    "is_auto_increment": true,
+                })),
+                column("region", "text", json!({})),
+                column("code", "integer", json!({})),
+                column("note", "text", json!({
+                    "is_nullable": true,
+                    "default_value": "'n/a'::text",
+                    "raw_default": "'n/a'::text",
+                    "comment": "Shown on the invoice",
+                })),
+                column("doubled", "integer", json!({
+                    "generation_expression": "(code * 2)",
+                    "is_generated": true,
+                })),
+            ],
+            "indexes": [
+                {
+                    "name": "orders_pkey",
+                    "is_unique": true,
+                    "is_primary": true,
+                    "definition": "CREATE UNIQUE INDEX orders_pkey ON public.orders USING btree (id)",
+                },
+                {
+                    "name": "orders_note_key",
+                    "is_unique": true,
+                    "is_primary": false,
+                    "definition": "CREATE UNIQUE INDEX orders_note_key ON public.orders USING btree (note)",
+                },
+                {
+                    "name": "orders_code_idx",
+                    "is_unique": false,
+                    "is_primary": false,
+                    "definition": "CREATE INDEX orders_code_idx ON public.orders USING btree (code)",
+                },
+            ],
+            "constraints": [
+                { "name": "orders_note_key", "constraint_type": "UNIQUE", "definition": "UNIQUE (note)" },
+                { "name": "orders_code_check", "constraint_type": "CHECK", "definition": "CHECK ((code > 0))" },
+            ],
+            "foreign_keys": [
+                { "name": "orders_area_fkey", "column_name": "region", "ref_schema": "public", "ref_table": "areas", "ref_column": "region" },
+                { "name": "orders_area_fkey", "column_name": "code", "ref_schema": "public", "ref_table": "areas", "ref_column": "code" },
+            ],
+            "table_comment": "Customer's orders",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ddl_recreates_the_table() {
+        assert_eq!(
+            table_ddl("public", "orders", &orders()),
+            r#"CREATE TABLE "public"."orders" (
+    "id" serial NOT NULL,
+    "region" text NOT NULL,
+    "code" integer NOT NULL,
+    "note" text DEFAULT 'n/a'::text,
+    "doubled" integer GENERATED ALWAYS AS ((code * 2)) STORED NOT NULL
+);
+
+ALTER TABLE "public"."orders" ADD CONSTRAINT "orders_pkey" PRIMARY KEY (id);
+ALTER TABLE "public"."orders" ADD CONSTRAINT "orders_note_key" UNIQUE (note);
+ALTER TABLE "public"."orders" ADD CONSTRAINT "orders_code_check" CHECK ((code > 0));
+ALTER TABLE "public"."orders" ADD CONSTRAINT "orders_area_fkey" FOREIGN KEY ("region", "code") REFERENCES "public"."areas" ("region", "code");
+CREATE INDEX orders_code_idx ON public.orders USING btree (code);
+COMMENT ON TABLE "public"."orders" IS 'Customer''s orders';
+COMMENT ON COLUMN "public"."orders"."note" IS 'Shown on the invoice';
+"#
+        );
+    }
+
+    #[test]
+    fn identity_columns_keep_their_generation() {
+        let mut structure = orders();
+        structure.columns[0].raw_default = None;
+        structure.columns[0].identity_generation = Some("BY DEFAULT".into());
+        let ddl = table_ddl("public", "orders", &structure);
+        assert!(ddl.contains("\"id\" integer GENERATED BY DEFAULT AS IDENTITY NOT NULL,"));
+    }
+}
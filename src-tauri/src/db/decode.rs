@@ -0,0 +1,678 @@
+use serde_json::Value as JsonValue;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::types::PgInterval;
+use sqlx::postgres::{PgRow, PgTypeInfo, PgTypeKind, PgValueFormat, PgValueRef};
+use sqlx::{Column, Decode, Postgres, Row, TypeInfo, ValueRef};
+
+/// Converts one non-NULL value of a known column type into JSON.
+pub type DecodeFn = fn(PgValueRef<'_>) -> Result<JsonValue, BoxDynError>;
+
+/// Resolve the decoder for a column from its type, once per result set.
+/// The row loop then applies it to every cell instead of probing types per value.
+pub fn decoder_for(type_info: &PgTypeInfo) -> DecodeFn {
+    match type_info.name() {
+        "BOOL" => decode_json::<bool>,
+        "INT2" => decode_json::<i16>,
+        "INT4" => decode_json::<i32>,
+        "INT8" => decode_json::<i64>,
+        "FLOAT4" => decode_json::<f32>,
+        "FLOAT8" => decode_json::<f64>,
+        "NUMERIC" => decode_numeric,
+        "MONEY" => decode_money,
+        "BYTEA" => decode_bytea,
+        "UUID" => decode_display::<uuid::Uuid>,
+        "TIMESTAMPTZ" => decode_timestamptz,
+        "TIMESTAMP" => decode_timestamp,
+        "DATE" => decode_date,
+        "TIME" => decode_display::<chrono::NaiveTime>,
+        "INTERVAL" => decode_interval,
+        "INET" | "CIDR" => decode_inet,
+        "MACADDR" | "MACADDR8" => decode_macaddr,
+        "JSON" | "JSONB" => decode_json::<JsonValue>,
+        "TEXT" | "VARCHAR" | "CHAR" | "\"CHAR\"" | "NAME" | "UNKNOWN" | "CITEXT" => {
+            decode_json::<String>
+        }
+        // One-dimensional arrays become JSON arrays with null elements preserved.
+        // Multidimensional arrays aren't supported by sqlx's Vec decoding and come back null.
+        "BOOL[]" => decode_json::<Vec<Option<bool>>>,
+        "INT2[]" => decode_json::<Vec<Option<i16>>>,
+        "INT4[]" => decode_json::<Vec<Option<i32>>>,
+        "INT8[]" => decode_json::<Vec<Option<i64>>>,
+        "FLOAT4[]" => decode_json::<Vec<Option<f32>>>,
+        "FLOAT8[]" => decode_json::<Vec<Option<f64>>>,
+        "TEXT[]" | "VARCHAR[]" | "CHAR[]" | "NAME[]" => decode_json::<Vec<Option<String>>>,
+        "UUID[]" => decode_uuid_array,
+        "RECORD" => decode_record,
+        _ => match type_info.kind() {
+            PgTypeKind::Enum(_) => decode_json::<String>,
+            PgTypeKind::Composite(_) => decode_record,
+            PgTypeKind::Range(_) => decode_range,
+            PgTypeKind::Domain(base) => decoder_for(base),
+            _ => decode_text,
+        },
+    }
+}
+
+/// Column names and decoders for a result set, taken from its first row.
+pub fn row_decoders(row: &PgRow) -> (Vec<String>, Vec<DecodeFn>) {
+    row.columns()
+        .iter()
+        .map(|col| (col.name().to_string(), decoder_for(col.type_info())))
+        .unzip()
+}
+
+/// Decode every cell of a row with the decoders from `row_decoders`.
+pub fn decode_row(row: &PgRow, decoders: &[DecodeFn]) -> Vec<JsonValue> {
+    decoders
+        .iter()
+        .enumerate()
+        .map(|(i, decoder)| decode_cell(row, i, *decoder))
+        .collect()
+}
+
+/// Decode a single cell with a decoder from `decoder_for`.
+/// NULLs and values the decoder can't read come back as JSON null.
+pub fn decode_cell(row: &PgRow, index: usize, decoder: DecodeFn) -> JsonValue {
+    match row.try_get_raw(index) {
+        Ok(value) if !value.is_null() => decoder(value).unwrap_or(JsonValue::Null),
+        _ => JsonValue::Null,
+    }
+}
+
+fn decode_json<T>(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError>
+where
+    T: for<'r> Decode<'r, Postgres> + serde::Serialize,
+{
+    Ok(serde_json::to_value(T::decode(value)?)?)
+}
+
+fn decode_display<T>(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError>
+where
+    T: for<'r> Decode<'r, Postgres> + std::fmt::Display,
+{
+    Ok(JsonValue::String(T::decode(value)?.to_string()))
+}
+
+// DATE and TIMESTAMP[TZ] are decoded here rather than by sqlx, which panics on
+// `infinity` and `-infinity` (stored as the integer type's MAX and MIN).
+fn decode_timestamptz(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => decode_display::<chrono::DateTime<chrono::Utc>>(value),
+        PgValueFormat::Binary => {
+            let micros = <i64 as Decode<'_, Postgres>>::decode(value)?;
+            Ok(JsonValue::String(timestamp_to_string(micros, true)?))
+        }
+    }
+}
+
+fn decode_timestamp(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => decode_display::<chrono::NaiveDateTime>(value),
+        PgValueFormat::Binary => {
+            let micros = <i64 as Decode<'_, Postgres>>::decode(value)?;
+            Ok(JsonValue::String(timestamp_to_string(micros, false)?))
+        }
+    }
+}
+
+fn decode_date(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => decode_display::<chrono::NaiveDate>(value),
+        PgValueFormat::Binary => {
+            let days = <i32 as Decode<'_, Postgres>>::decode(value)?;
+            Ok(JsonValue::String(date_to_string(days)?))
+        }
+    }
+}
+
+fn postgres_epoch() -> chrono::NaiveDate {
+    chrono::NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date")
+}
+
+/// Text form of a binary DATE, days since 2000-01-01.
+fn date_to_string(days: i32) -> Result<String, BoxDynError> {
+    Ok(match days {
+        i32::MAX => "infinity".into(),
+        i32::MIN => "-infinity".into(),
+        _ => postgres_epoch()
+            .checked_add_signed(chrono::Duration::days(days.into()))
+            .ok_or("date out of range")?
+            .to_string(),
+    })
+}
+
+/// Text form of a binary TIMESTAMP, or of a TIMESTAMPTZ in RFC 3339 when `utc` is set;
+/// both are microseconds since 2000-01-01 00:00 (UTC).
+fn timestamp_to_string(micros: i64, utc: bool) -> Result<String, BoxDynError> {
+    Ok(match micros {
+        i64::MAX => "infinity".into(),
+        i64::MIN => "-infinity".into(),
+        _ => {
+            let ts = postgres_epoch()
+                .and_time(chrono::NaiveTime::MIN)
+                .checked_add_signed(chrono::Duration::microseconds(micros))
+                .ok_or("timestamp out of range")?;
+            if utc {
+                ts.and_utc().to_rfc3339()
+            } else {
+                ts.to_string()
+            }
+        }
+    })
+}
+
+/// Render INTERVAL as an ISO-8601 duration the way Postgres does with
+/// `intervalstyle = iso_8601`, e.g. `P1Y2M3DT4H5M6S`; components can be negative.
+fn decode_interval(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    let v = <PgInterval as Decode<'_, Postgres>>::decode(value)?;
+    Ok(JsonValue::String(interval_to_iso8601(&v)))
+}
+
+fn interval_to_iso8601(v: &PgInterval) -> String {
+    use std::fmt::Write;
+
+    const MICROS_PER_MINUTE: i64 = 60_000_000;
+    const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+
+    if v.months == 0 && v.days == 0 && v.microseconds == 0 {
+        return "PT0S".into();
+    }
+
+    let mut out = String::from("P");
+    let (years, months) = (v.months / 12, v.months % 12);
+    for (n, unit) in [(years, 'Y'), (months, 'M'), (v.days, 'D')] {
+        if n != 0 {
+            let _ = write!(out, "{}{}", n, unit);
+        }
+    }
+
+    if v.microseconds != 0 {
+        out.push('T');
+        let hours = v.microseconds / MICROS_PER_HOUR;
+        let minutes = v.microseconds % MICROS_PER_HOUR / MICROS_PER_MINUTE;
+        let micros = v.microseconds % MICROS_PER_MINUTE;
+        for (n, unit) in [(hours, 'H'), (minutes, 'M')] {
+            if n != 0 {
+                let _ = write!(out, "{}{}", n, unit);
+            }
+        }
+        if micros != 0 {
+            let sign = if micros < 0 { "-" } else { "" };
+            let (secs, frac) = (micros.abs() / 1_000_000, micros.abs() % 1_000_000);
+            if frac == 0 {
+                let _ = write!(out, "{}{}S", sign, secs);
+            } else {
+                let frac = format!("{:06}", frac);
+                let _ = write!(out, "{}{}.{}S", sign, secs, frac.trim_end_matches('0'));
+            }
+        }
+    }
+    out
+}
+
+/// Render INET/CIDR like Postgres: `192.168.0.1`, `192.168.0.1/24`, `10.0.0.0/8`.
+/// The netmask is omitted for an inet host address and always shown for cidr.
+fn decode_inet(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    if value.format() == PgValueFormat::Text {
+        return Ok(JsonValue::String(value.as_str()?.to_string()));
+    }
+
+    // Binary layout: family, netmask bits, is_cidr flag, address length, address bytes.
+    let buf = value.as_bytes()?;
+    let [family, bits, is_cidr, len, addr @ ..] = buf else {
+        return Err("truncated INET value".into());
+    };
+    let addr: std::net::IpAddr = match (*family, *len) {
+        (2, 4) => <[u8; 4]>::try_from(addr)?.into(),
+        (3, 16) => <[u8; 16]>::try_from(addr)?.into(),
+        _ => return Err(format!("unsupported INET address family {}", family).into()),
+    };
+    let max_bits = if addr.is_ipv4() { 32 } else { 128 };
+    Ok(JsonValue::String(if *is_cidr != 0 || *bits != max_bits {
+        format!("{}/{}", addr, bits)
+    } else {
+        addr.to_string()
+    }))
+}
+
+/// Render MACADDR / MACADDR8 as lowercase colon-separated hex, e.g. `08:00:2b:01:02:03`.
+fn decode_macaddr(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    if value.format() == PgValueFormat::Text {
+        return Ok(JsonValue::String(value.as_str()?.to_string()));
+    }
+    let octets: Vec<String> = value
+        .as_bytes()?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(JsonValue::String(octets.join(":")))
+}
+
+fn decode_uuid_array(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    let items = <Vec<Option<uuid::Uuid>> as Decode<'_, Postgres>>::decode(value)?;
+    Ok(JsonValue::Array(
+        items
+            .into_iter()
+            .map(|v| v.map_or(JsonValue::Null, |u| JsonValue::String(u.to_string())))
+            .collect(),
+    ))
+}
+
+/// Binary data is tagged so the frontend can tell it apart from text:
+/// `{ "type": "bytea", "base64": "..." }`.
+fn decode_bytea(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    use base64::Engine;
+
+    let bytes = <Vec<u8> as Decode<'_, Postgres>>::decode(value)?;
+    Ok(serde_json::json!({
+        "type": "bytea",
+        "base64": base64::engine::general_purpose::STANDARD.encode(bytes),
+    }))
+}
+
+/// Render NUMERIC as a decimal string so every digit survives; going through f64
+/// would round anything beyond ~15 significant digits.
+fn decode_numeric(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => Ok(JsonValue::String(value.as_str()?.to_string())),
+        PgValueFormat::Binary => Ok(JsonValue::String(numeric_to_string(value.as_bytes()?)?)),
+    }
+}
+
+/// Render MONEY as a plain decimal string (`1234.56`), without currency symbol or
+/// grouping. The binary value is an integer count of minor units; two fractional digits
+/// are assumed, as for every lc_monetary with cents. Text values are stripped of
+/// everything but digits, sign and the decimal point.
+fn decode_money(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => {
+            let text = value.as_str()?;
+            let negative = text.contains('-') || text.starts_with('(');
+            let digits: String = text
+                .chars()
+                .filter(|c| c.is_ascii_digit() || *c == '.')
+                .collect();
+            Ok(JsonValue::String(if negative {
+                format!("-{}", digits)
+            } else {
+                digits
+            }))
+        }
+        PgValueFormat::Binary => {
+            let cents = <i64 as Decode<'_, Postgres>>::decode(value)?;
+            let sign = if cents < 0 { "-" } else { "" };
+            let cents = cents.unsigned_abs();
+            Ok(JsonValue::String(format!(
+                "{}{}.{:02}",
+                sign,
+                cents / 100,
+                cents % 100
+            )))
+        }
+    }
+}
+
+/// Format the binary NUMERIC wire representation: ndigits, weight, sign, dscale
+/// (all 16-bit) followed by base-10000 digits, the first having the given weight.
+fn numeric_to_string(buf: &[u8]) -> Result<String, BoxDynError> {
+    use std::fmt::Write;
+
+    let read_i16 = |at: usize| -> Result<i16, BoxDynError> {
+        buf.get(at..at + 2)
+            .map(|b| i16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| "truncated NUMERIC value".into())
+    };
+
+    let ndigits = read_i16(0)?.max(0) as usize;
+    let weight = read_i16(2)? as i32;
+    let sign = read_i16(4)? as u16;
+    let dscale = read_i16(6)?.max(0) as usize;
+    let digits = (0..ndigits)
+        .map(|i| read_i16(8 + i * 2))
+        .collect::<Result<Vec<i16>, _>>()?;
+
+    match sign {
+        0xC000 => return Ok("NaN".into()),
+        0xD000 => return Ok("Infinity".into()),
+        0xF000 => return Ok("-Infinity".into()),
+        _ => {}
+    }
+
+    let digit_at = |idx: i32| -> i16 {
+        if idx < 0 {
+            0
+        } else {
+            digits.get(idx as usize).copied().unwrap_or(0)
+        }
+    };
+
+    let mut out = String::new();
+    if sign == 0x4000 {
+        out.push('-');
+    }
+    if weight < 0 {
+        out.push('0');
+    } else {
+        for idx in 0..=weight {
+            if idx == 0 {
+                write!(out, "{}", digit_at(idx))?;
+            } else {
+                write!(out, "{:04}", digit_at(idx))?;
+            }
+        }
+    }
+    if dscale > 0 {
+        let mut frac = String::with_capacity(dscale + 4);
+        let mut idx = weight + 1;
+        while frac.len() < dscale {
+            write!(frac, "{:04}", digit_at(idx))?;
+            idx += 1;
+        }
+        frac.truncate(dscale);
+        out.push('.');
+        out.push_str(&frac);
+    }
+    Ok(out)
+}
+
+/// Render a composite or anonymous record (`SELECT (a, b)`) as its tuple literal, e.g.
+/// `(1,foo)`, quoted the way Postgres does. The binary form is a field count followed by
+/// (type oid, length, bytes) per field. Only fields of basic types (bool, integers,
+/// floats, numeric, text types, uuid, date, timestamp[tz]) and nested anonymous records
+/// can be rendered; a record with any other field type comes back null.
+fn decode_record(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => Ok(JsonValue::String(value.as_str()?.to_string())),
+        PgValueFormat::Binary => Ok(JsonValue::String(record_to_string(value.as_bytes()?)?)),
+    }
+}
+
+fn record_to_string(buf: &[u8]) -> Result<String, BoxDynError> {
+    let count = i32::from_be_bytes(buf.get(..4).ok_or("truncated record value")?.try_into()?);
+    let mut fields = Vec::with_capacity(count.max(0) as usize);
+    let mut offset = 4;
+    for _ in 0..count {
+        let header = buf
+            .get(offset..offset + 8)
+            .ok_or("truncated record value")?;
+        let oid = u32::from_be_bytes(header[..4].try_into()?);
+        let len = i32::from_be_bytes(header[4..].try_into()?);
+        offset += 8;
+        if len < 0 {
+            // NULL fields are left empty in the literal.
+            fields.push(String::new());
+            continue;
+        }
+        let data = buf
+            .get(offset..offset + len as usize)
+            .ok_or("truncated record value")?;
+        offset += len as usize;
+        fields.push(quote_field(
+            &record_field_to_string(oid, data)?,
+            &['(', ')', ','],
+        ));
+    }
+    Ok(format!("({})", fields.join(",")))
+}
+
+/// Text form of one binary record field or range bound, by its type oid.
+fn record_field_to_string(oid: u32, data: &[u8]) -> Result<String, BoxDynError> {
+    Ok(match oid {
+        16 => if data.first() == Some(&1) { "t" } else { "f" }.to_string(),
+        21 => i16::from_be_bytes(data.try_into()?).to_string(),
+        23 => i32::from_be_bytes(data.try_into()?).to_string(),
+        20 => i64::from_be_bytes(data.try_into()?).to_string(),
+        26 => u32::from_be_bytes(data.try_into()?).to_string(),
+        700 => f32::from_be_bytes(data.try_into()?).to_string(),
+        701 => f64::from_be_bytes(data.try_into()?).to_string(),
+        1700 => numeric_to_string(data)?,
+        // text, varchar, bpchar, name, unknown
+        25 | 1043 | 1042 | 19 | 705 => std::str::from_utf8(data)?.to_string(),
+        2950 => uuid::Uuid::from_slice(data)?.to_string(),
+        1082 => date_to_string(i32::from_be_bytes(data.try_into()?))?,
+        1114 | 1184 => timestamp_to_string(i64::from_be_bytes(data.try_into()?), oid == 1184)?,
+        2249 => record_to_string(data)?,
+        _ => return Err(format!("no decoder for record field type oid {}", oid).into()),
+    })
+}
+
+/// Double-quote a record field or range bound when Postgres would: if it is empty or
+/// contains quotes, backslashes, whitespace or one of `delimiters`. Quotes and
+/// backslashes inside are doubled.
+fn quote_field(field: &str, delimiters: &[char]) -> String {
+    let needs_quotes = field.is_empty()
+        || field
+            .chars()
+            .any(|c| matches!(c, '"' | '\\') || c.is_whitespace() || delimiters.contains(&c));
+    if !needs_quotes {
+        return field.to_string();
+    }
+    let mut quoted = String::with_capacity(field.len() + 2);
+    quoted.push('"');
+    for c in field.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Render a range (int4range, daterange, tstzrange, ...) as its literal, e.g. `[1,10)` or
+/// `empty`. The binary form is a flags byte followed by (length, bytes) for each finite
+/// bound. Bounds are rendered like record fields, so ranges over types without a decoder
+/// there come back null.
+fn decode_range(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => Ok(JsonValue::String(value.as_str()?.to_string())),
+        PgValueFormat::Binary => {
+            let element_oid = match value.type_info().kind() {
+                PgTypeKind::Range(element) => element.oid().map(|oid| oid.0),
+                _ => None,
+            }
+            .ok_or("unknown range element type")?;
+            let buf = value.as_bytes()?;
+            Ok(JsonValue::String(range_to_string(element_oid, buf)?))
+        }
+    }
+}
+
+fn range_to_string(element_oid: u32, buf: &[u8]) -> Result<String, BoxDynError> {
+    const EMPTY: u8 = 0x01;
+    const LOWER_INC: u8 = 0x02;
+    const UPPER_INC: u8 = 0x04;
+    const LOWER_INF: u8 = 0x08;
+    const UPPER_INF: u8 = 0x10;
+
+    let flags = *buf.first().ok_or("truncated range value")?;
+    if flags & EMPTY != 0 {
+        return Ok("empty".into());
+    }
+    let mut offset = 1;
+    let mut bound = |infinite: bool| -> Result<String, BoxDynError> {
+        if infinite {
+            return Ok(String::new());
+        }
+        let len = i32::from_be_bytes(
+            buf.get(offset..offset + 4)
+                .ok_or("truncated range value")?
+                .try_into()?,
+        );
+        offset += 4;
+        let data = buf
+            .get(offset..offset + len.max(0) as usize)
+            .ok_or("truncated range value")?;
+        offset += data.len();
+        Ok(quote_field(
+            &record_field_to_string(element_oid, data)?,
+            &['(', ')', '[', ']', ','],
+        ))
+    };
+    let lower = bound(flags & LOWER_INF != 0)?;
+    let upper = bound(flags & UPPER_INF != 0)?;
+    Ok(format!(
+        "{}{},{}{}",
+        if flags & LOWER_INC != 0 { '[' } else { '(' },
+        lower,
+        upper,
+        if flags & UPPER_INC != 0 { ']' } else { ')' },
+    ))
+}
+
+/// Types without a dedicated decoder are only readable when the server sent them as text.
+fn decode_text(value: PgValueRef<'_>) -> Result<JsonValue, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Text => Ok(JsonValue::String(value.as_str()?.to_string())),
+        PgValueFormat::Binary => {
+            Err(format!("no decoder for type {}", value.type_info().name()).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(months: i32, days: i32, microseconds: i64) -> PgInterval {
+        PgInterval {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    #[test]
+    fn interval_as_iso8601() {
+        let hms = (4 * 3600 + 5 * 60 + 6) * 1_000_000;
+        assert_eq!(interval_to_iso8601(&interval(14, 3, hms)), "P1Y2M3DT4H5M6S");
+        assert_eq!(interval_to_iso8601(&interval(0, 0, 0)), "PT0S");
+        assert_eq!(interval_to_iso8601(&interval(0, 0, 90_000_000)), "PT1M30S");
+        assert_eq!(interval_to_iso8601(&interval(0, 0, 1_250)), "PT0.00125S");
+        assert_eq!(
+            interval_to_iso8601(&interval(-1, 0, -1_500_000)),
+            "P-1MT-1.5S"
+        );
+    }
+
+    /// Binary NUMERIC: ndigits, weight, sign, dscale, then base-10000 digits.
+    fn numeric(weight: i16, sign: u16, dscale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for n in [digits.len() as i16, weight, sign as i16, dscale] {
+            buf.extend(n.to_be_bytes());
+        }
+        for d in digits {
+            buf.extend(d.to_be_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn numeric_keeps_every_digit() {
+        let cases = [
+            (numeric(1, 0, 3, &[1, 2345, 6780]), "12345.678"),
+            (numeric(-1, 0x4000, 2, &[500]), "-0.05"),
+            (numeric(0, 0, 0, &[]), "0"),
+            (numeric(2, 0, 2, &[7]), "700000000.00"),
+            (
+                numeric(7, 0, 0, &[12, 3456, 7890, 1234, 5678, 9012, 3456, 7890]),
+                "123456789012345678901234567890",
+            ),
+            (numeric(0, 0xC000, 0, &[]), "NaN"),
+            (numeric(0, 0xF000, 0, &[]), "-Infinity"),
+        ];
+        for (buf, expected) in cases {
+            assert_eq!(numeric_to_string(&buf).unwrap(), expected);
+        }
+        assert!(numeric_to_string(&[0, 1]).is_err());
+    }
+
+    /// Binary range: flags byte, then (length, bytes) for each finite bound.
+    fn range(flags: u8, bounds: &[&[u8]]) -> Vec<u8> {
+        let mut buf = vec![flags];
+        for bound in bounds {
+            buf.extend((bound.len() as i32).to_be_bytes());
+            buf.extend(*bound);
+        }
+        buf
+    }
+
+    #[test]
+    fn range_literals() {
+        let int4 = |n: i32| n.to_be_bytes();
+        assert_eq!(
+            range_to_string(23, &range(0x02, &[&int4(1), &int4(10)])).unwrap(),
+            "[1,10)"
+        );
+        assert_eq!(range_to_string(23, &range(0x01, &[])).unwrap(), "empty");
+        // daterange '[2000-01-01,)': the upper bound is infinite and has no bytes.
+        assert_eq!(
+            range_to_string(1082, &range(0x12, &[&0i32.to_be_bytes()])).unwrap(),
+            "[2000-01-01,)"
+        );
+        assert_eq!(
+            range_to_string(25, &range(0x06, &[b"a b", b"c,d"])).unwrap(),
+            "[\"a b\",\"c,d\"]"
+        );
+        assert!(range_to_string(23, &range(0x02, &[&int4(1)])).is_err());
+    }
+
+    #[test]
+    fn infinite_dates_and_timestamps() {
+        assert_eq!(date_to_string(i32::MAX).unwrap(), "infinity");
+        assert_eq!(date_to_string(i32::MIN).unwrap(), "-infinity");
+        assert_eq!(date_to_string(-1).unwrap(), "1999-12-31");
+        assert!(date_to_string(i32::MAX - 1).is_err());
+        assert_eq!(timestamp_to_string(i64::MAX, false).unwrap(), "infinity");
+        assert_eq!(timestamp_to_string(i64::MIN, true).unwrap(), "-infinity");
+        assert_eq!(
+            timestamp_to_string(90_000_000, true).unwrap(),
+            "2000-01-01T00:01:30+00:00"
+        );
+    }
+
+    async fn select_row(sql: &str) -> Vec<JsonValue> {
+        let url = std::env::var("BESTGRES_TEST_DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        let row = sqlx::query(sql).fetch_one(&pool).await.unwrap();
+        let (_, decoders) = row_decoders(&row);
+        decode_row(&row, &decoders)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn record_literals_with_infinite_fields() {
+        let row = select_row(
+            "SELECT ROW(1, 'a b', DATE 'infinity', TIMESTAMP '-infinity', NULL::int), \
+                    DATE '-infinity', TIMESTAMPTZ 'infinity'",
+        )
+        .await;
+        assert_eq!(
+            row,
+            vec![
+                JsonValue::from("(1,\"a b\",infinity,-infinity,)"),
+                JsonValue::from("-infinity"),
+                JsonValue::from("infinity"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn range_literals_from_the_server() {
+        let row = select_row(
+            "SELECT int4range(1, 10), int4range(5, NULL), \
+                    daterange('2020-01-01', 'infinity'), tsrange('-infinity', 'infinity')",
+        )
+        .await;
+        assert_eq!(
+            row,
+            vec![
+                JsonValue::from("[1,10)"),
+                JsonValue::from("[5,)"),
+                JsonValue::from("[2020-01-01,infinity)"),
+                JsonValue::from("[-infinity,infinity)"),
+            ]
+        );
+    }
+}
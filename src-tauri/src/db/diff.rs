@@ -0,0 +1,171 @@
+use crate::models::{
+    ColumnDetail, ConstraintInfo, IndexInfo, ItemChange, SchemaDiff, TableStructure,
+};
+
+/// Compare two table structures, matching columns, indexes and constraints by name.
+/// Indexes are compared on uniqueness and their definition from `USING` on, so the same
+/// index on differently named tables or schemas doesn't show as changed.
+pub fn diff_structures(a: &TableStructure, b: &TableStructure) -> SchemaDiff {
+    let (added_columns, removed_columns, changed_columns) =
+        diff_items(&a.columns, &b.columns, |c| &c.name, same_column);
+    let (added_indexes, removed_indexes, changed_indexes) =
+        diff_items(&a.indexes, &b.indexes, |i| &i.name, same_index);
+    let (added_constraints, removed_constraints, changed_constraints) =
+        diff_items(&a.constraints, &b.constraints, |c| &c.name, same_constraint);
+    SchemaDiff {
+        added_columns,
+        removed_columns,
+        changed_columns,
+        added_indexes,
+        removed_indexes,
+        changed_indexes,
+        added_constraints,
+        removed_constraints,
+        changed_constraints,
+    }
+}
+
+type ItemDiff<T> = (Vec<T>, Vec<T>, Vec<ItemChange<T>>);
+
+/// Items only in `b` (added), only in `a` (removed), and in both but not `same`
+/// (changed), each in the order of the list they come from.
+fn diff_items<T: Clone>(
+    a: &[T],
+    b: &[T],
+    name: impl Fn(&T) -> &String,
+    same: impl Fn(&T, &T) -> bool,
+) -> ItemDiff<T> {
+    let find = |items: &[T], wanted: &String| items.iter().find(|i| name(i) == wanted).cloned();
+
+    let added = b
+        .iter()
+        .filter(|item| find(a, name(item)).is_none())
+        .cloned()
+        .collect();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    for before in a {
+        match find(b, name(before)) {
+            None => removed.push(before.clone()),
+            Some(after) if !same(before, &after) => changed.push(ItemChange {
+                name: name(before).clone(),
+                before: before.clone(),
+                after,
+            }),
+            Some(_) => {}
+        }
+    }
+    (added, removed, changed)
+}
+
+/// Type, nullability and default. Sequence defaults are compared through
+/// `is_auto_increment`, since the sequence names differ from table to table.
+fn same_column(a: &ColumnDetail, b: &ColumnDetail) -> bool {
+    a.data_type == b.data_type
+        && a.is_nullable == b.is_nullable
+        && a.default_value == b.default_value
+        && a.is_auto_increment == b.is_auto_increment
+        && a.generation_expression == b.generation_expression
+}
+
+fn same_index(a: &IndexInfo, b: &IndexInfo) -> bool {
+    let method_and_key = |i: &IndexInfo| {
+        i.definition
+            .split_once(" USING ")
+            .map(|(_, rest)| rest.to_string())
+    };
+    a.is_unique == b.is_unique
+        && a.is_primary == b.is_primary
+        && method_and_key(a) == method_and_key(b)
+}
+
+fn same_constraint(a: &ConstraintInfo, b: &ConstraintInfo) -> bool {
+    a.constraint_type == b.constraint_type && a.definition == b.definition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn structure(table: &str, columns: serde_json::Value, check: &str) -> TableStructure {
+        serde_json::from_value(json!({
+            "columns": columns,
+            "indexes": [{
+                "name": "by_b",
+                "is_unique": false,
+                "is_primary": false,
+                "definition": format!("CREATE INDEX by_b ON public.{} USING btree (b)", table),
+            }],
+            "constraints": [{
+                "name": "b_check",
+                "constraint_type": "CHECK",
+                "definition": check,
+            }],
+            "foreign_keys": [],
+        }))
+        .unwrap()
+    }
+
+    fn column(name: &str, data_type: &str, is_nullable: bool) -> serde_json::Value {
+        json!({
+            "name": name,
+            "data_type": data_type,
+            "is_nullable": is_nullable,
+            "default_value": null,
+        })
+    }
+
+    #[test]
+    fn diff_matches_items_by_name() {
+        let a = structure(
+            "orders",
+            json!([
+                column("id", "integer", false),
+                column("b", "text", true),
+                column("old", "text", true)
+            ]),
+            "CHECK ((b > 0))",
+        );
+        let b = structure(
+            "orders_copy",
+            json!([
+                column("id", "integer", false),
+                column("b", "text", false),
+                column("new", "date", true)
+            ]),
+            "CHECK ((b >= 0))",
+        );
+        let diff = diff_structures(&a, &b);
+
+        let names = |columns: &[ColumnDetail]| -> Vec<String> {
+            columns.iter().map(|c| c.name.clone()).collect()
+        };
+        assert_eq!(names(&diff.added_columns), ["new"]);
+        assert_eq!(names(&diff.removed_columns), ["old"]);
+        assert_eq!(diff.changed_columns.len(), 1);
+        assert_eq!(diff.changed_columns[0].name, "b");
+        assert!(diff.changed_columns[0].before.is_nullable);
+        assert!(!diff.changed_columns[0].after.is_nullable);
+
+        // Same index on a differently named table isn't a change.
+        assert!(diff.changed_indexes.is_empty());
+        assert_eq!(diff.changed_constraints.len(), 1);
+        assert_eq!(
+            diff.changed_constraints[0].after.definition,
+            "CHECK ((b >= 0))"
+        );
+    }
+
+    #[test]
+    fn identical_structures_have_no_diff() {
+        let columns = json!([column("id", "integer", false)]);
+        let diff = diff_structures(
+            &structure("a", columns.clone(), "CHECK ((id > 0))"),
+            &structure("b", columns, "CHECK ((id > 0))"),
+        );
+        assert!(diff.added_columns.is_empty() && diff.removed_columns.is_empty());
+        assert!(diff.changed_columns.is_empty() && diff.changed_constraints.is_empty());
+        assert!(diff.added_indexes.is_empty() && diff.removed_indexes.is_empty());
+    }
+}
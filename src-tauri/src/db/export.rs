@@ -0,0 +1,202 @@
+use serde_json::Value as JsonValue;
+
+use crate::models::{ExportFormat, QueryResult};
+
+/// Plain-text form of a cell for delimited/tabular output. NULL becomes an empty string.
+pub fn cell_to_text(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => String::new(),
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Quote a CSV field per RFC 4180 when it contains a delimiter, quote, or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// TSV has no quoting, so tabs and line breaks inside a cell are flattened to spaces.
+fn tsv_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+/// One CSV record (without the trailing line break).
+pub fn csv_record(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// One TSV record (without the trailing line break).
+pub fn tsv_record(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|f| tsv_field(f))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+/// Header plus one line per row, CSV (`tsv == false`) or TSV.
+pub fn delimited_table(result: &QueryResult, tsv: bool) -> String {
+    let record: fn(&[String]) -> String = if tsv { tsv_record } else { csv_record };
+
+    let mut out = record(&result.columns);
+    out.push('\n');
+    for row in &result.rows {
+        let cells: Vec<String> = row.iter().map(cell_to_text).collect();
+        out.push_str(&record(&cells));
+        out.push('\n');
+    }
+    out
+}
+
+/// Escape a Markdown table cell: pipes are escaped and line breaks become `<br>`.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+/// A GitHub-flavored Markdown table. NULL cells are shown as `NULL`.
+pub fn markdown_table(result: &QueryResult) -> String {
+    let header: Vec<String> = result.columns.iter().map(|c| markdown_cell(c)).collect();
+    let mut out = format!("| {} |\n", header.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(result.columns.len())));
+    for row in &result.rows {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|v| match v {
+                JsonValue::Null => "NULL".to_string(),
+                other => markdown_cell(&cell_to_text(other)),
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// A whole result as text in the given format, e.g. for the clipboard. NULL cells are
+/// empty in CSV/TSV, `NULL` in Markdown and `null` in JSON (an array of row objects).
+pub fn format_result(result: &QueryResult, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => delimited_table(result, false),
+        ExportFormat::Tsv => delimited_table(result, true),
+        ExportFormat::Markdown => markdown_table(result),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&row_objects(result)).unwrap_or_default() + "\n"
+        }
+    }
+}
+
+/// One row as a JSON object keyed by column name. NULLs are kept as `null`.
+pub fn row_object(columns: &[String], row: Vec<JsonValue>) -> JsonValue {
+    JsonValue::Object(columns.iter().cloned().zip(row).collect())
+}
+
+/// Rows as JSON objects keyed by column name. NULLs are kept as `null`.
+pub fn row_objects(result: &QueryResult) -> Vec<JsonValue> {
+    result
+        .rows
+        .iter()
+        .map(|row| row_object(&result.columns, row.clone()))
+        .collect()
+}
+
+/// One labeled block of a multi-query export in a text format (CSV, TSV or Markdown).
+/// A failed query is written as its error message in place of the table.
+/// JSON exports are assembled as a single object by the caller instead.
+pub fn labeled_section(
+    label: &str,
+    result: Result<&QueryResult, &str>,
+    format: ExportFormat,
+) -> String {
+    match format {
+        ExportFormat::Markdown => {
+            let body = match result {
+                Ok(r) => markdown_table(r),
+                Err(e) => format!("> Error: {}\n", markdown_cell(e)),
+            };
+            format!("## {}\n\n{}\n", label, body)
+        }
+        _ => {
+            let tsv = format == ExportFormat::Tsv;
+            let record: fn(&[String]) -> String = if tsv { tsv_record } else { csv_record };
+            let body = match result {
+                Ok(r) => delimited_table(r, tsv),
+                Err(e) => format!("{}\n", record(&["ERROR".to_string(), e.to_string()])),
+            };
+            format!("{}\n{}\n", record(&[label.to_string()]), body)
+        }
+    }
+}
+
+/// Render a value read as text as a SQL literal for a column of the given base type.
+/// Integers, finite floats/numerics and booleans are written bare; everything else
+/// (including NaN and Infinity) is a quoted string with `'` doubled.
+pub fn sql_literal(value: Option<&str>, type_name: &str) -> String {
+    let Some(value) = value else {
+        return "NULL".to_string();
+    };
+    match type_name {
+        "int2" | "int4" | "int8" | "oid" => value.to_string(),
+        "float4" | "float8" | "numeric" if value.parse::<f64>().is_ok_and(f64::is_finite) => {
+            value.to_string()
+        }
+        "bool" => value.to_uppercase(),
+        _ => format!("'{}'", value.replace('\'', "''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result() -> QueryResult {
+        serde_json::from_value(json!({
+            "columns": ["id", "note"],
+            "rows": [[1, "a|b"], [2, null], [3, "line\nbreak, \"quoted\""]],
+            "row_count": 3,
+            "execution_time_ms": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn markdown_escapes_pipes_and_line_breaks() {
+        assert_eq!(
+            format_result(&result(), ExportFormat::Markdown),
+            "| id | note |\n\
+             | --- | --- |\n\
+             | 1 | a\\|b |\n\
+             | 2 | NULL |\n\
+             | 3 | line<br>break, \"quoted\" |\n"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_and_tsv_flattens_cells() {
+        assert_eq!(
+            format_result(&result(), ExportFormat::Csv),
+            "id,note\n1,a|b\n2,\n3,\"line\nbreak, \"\"quoted\"\"\"\n"
+        );
+        assert_eq!(
+            format_result(&result(), ExportFormat::Tsv),
+            "id\tnote\n1\ta|b\n2\t\n3\tline break, \"quoted\"\n"
+        );
+    }
+
+    #[test]
+    fn json_keeps_nulls() {
+        let text = format_result(&result(), ExportFormat::Json);
+        let rows: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(rows[1], json!({ "id": 2, "note": null }));
+    }
+}
@@ -1 +1,6 @@
+pub mod ddl;
+pub mod decode;
+pub mod diff;
+pub mod export;
 pub mod postgres;
+pub mod script;
@@ -1,27 +1,92 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
-use sqlx::postgres::PgPoolOptions;
-use sqlx::{Column, PgPool, Row};
+use futures_util::TryStreamExt;
+use sqlx::postgres::{PgConnection, PgPoolCopyExt, PgPoolOptions};
+use sqlx::{Column, Connection, Execute, Executor, PgPool, Row, Statement, TypeInfo};
+use tokio::io::AsyncBufReadExt;
 
-use crate::models::{AppError, ColumnInfo, QueryResult, SchemaObject, SchemaObjectType};
+use crate::db::decode::{self, DecodeFn};
+use crate::db::export;
+use crate::db::script;
+use crate::models::{
+    AppError, CellEdit, ColumnFilter, ColumnInfo, ColumnSource, ConnectionConfig, CostEstimate,
+    DatabaseSize, EnumType, ExtensionInfo, FilterOp, LockInfo, MaintenanceOp, QueryResult,
+    RoleInfo, RoleMembership, SchemaObject, SchemaObjectType, ScriptResult, SequenceInfo,
+    ServerInfo, SessionInfo, StatementKind, StatementOutcome, TableSize,
+};
+
+/// Most bind parameters Postgres accepts in one statement.
+const MAX_BIND_PARAMS: usize = 65535;
+
+type PgQuery<'q> = sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>;
+
+/// Pool options shared by eager and lazy pools: the connection's pool size and acquire
+/// timeout (5 and 5s by default). For read-only connections every new session defaults to
+/// read-only transactions, so the server rejects writes from the editor.
+fn pool_options(config: &ConnectionConfig) -> PgPoolOptions {
+    let options = PgPoolOptions::new()
+        .max_connections(config.max_connections.unwrap_or(5))
+        .acquire_timeout(Duration::from_secs(
+            config.acquire_timeout_secs.unwrap_or(5),
+        ));
+    if !config.read_only {
+        return options;
+    }
+    options.after_connect(|conn, _meta| {
+        Box::pin(async move {
+            conn.execute("SET default_transaction_read_only = on")
+                .await?;
+            Ok(())
+        })
+    })
+}
 
 /// Create a new connection pool for the given connection string.
-/// Eagerly connects and validates the connection.
-pub async fn create_pool(connection_string: &str) -> Result<PgPool, AppError> {
-    PgPoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(5))
-        .connect(connection_string)
-        .await
-        .map_err(|e| AppError::Connection(e.to_string()))
+/// Eagerly connects and validates the connection. Transient failures are retried up to
+/// `retries` times, waiting `backoff` before the first retry and doubling it each time;
+/// anything else (bad password, TLS or config problems) fails at once.
+pub async fn create_pool(
+    connection_string: &str,
+    config: &ConnectionConfig,
+    retries: u32,
+    backoff: Duration,
+) -> Result<PgPool, AppError> {
+    let mut attempt = 0;
+    let mut delay = backoff;
+    loop {
+        match pool_options(config).connect(connection_string).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if attempt < retries && is_transient_connect_error(&e) => {
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+            Err(e) => return Err(AppError::Connection(e.to_string())),
+        }
+    }
+}
+
+/// Whether a failed connect might succeed if tried again: network errors (refused, reset,
+/// unreachable), timeouts, and the server refusing connections for now (SQLSTATE class 08,
+/// 53300 too_many_connections, 57P03 cannot_connect_now). Authentication errors are not.
+fn is_transient_connect_error(e: &sqlx::Error) -> bool {
+    match e {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db) => db
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code == "53300" || code == "57P03"),
+        _ => false,
+    }
 }
 
 /// Create a lazy connection pool that only connects when first used.
 /// Uses a short acquire timeout so unreachable hosts fail fast.
-pub fn create_pool_lazy(connection_string: &str) -> Result<PgPool, AppError> {
-    PgPoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(5))
+pub fn create_pool_lazy(
+    connection_string: &str,
+    config: &ConnectionConfig,
+) -> Result<PgPool, AppError> {
+    pool_options(config)
         .connect_lazy(connection_string)
         .map_err(|e| AppError::Connection(e.to_string()))
 }
@@ -35,6 +100,28 @@ pub async fn test_connection(pool: &PgPool) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Open one connection (no pool) and run SELECT 1 on it. A pool retries refused
+/// connections until its acquire timeout and then reports only "pool timed out", so this
+/// is what surfaces the driver's own error. Gives up after the connection's acquire timeout.
+pub async fn test_connection_string(
+    connection_string: &str,
+    config: &ConnectionConfig,
+) -> Result<(), AppError> {
+    let timeout = Duration::from_secs(config.acquire_timeout_secs.unwrap_or(5));
+    let attempt = async {
+        let mut conn = PgConnection::connect(connection_string).await?;
+        conn.execute("SELECT 1").await?;
+        conn.close().await
+    };
+    match tokio::time::timeout(timeout, attempt).await {
+        Ok(result) => result.map_err(|e| AppError::Connection(e.to_string())),
+        Err(_) => Err(AppError::Connection(format!(
+            "Timed out connecting after {} s",
+            timeout.as_secs()
+        ))),
+    }
+}
+
 /// List all non-template databases on the server.
 pub async fn list_databases(pool: &PgPool) -> Result<Vec<String>, AppError> {
     let rows = sqlx::query(
@@ -42,633 +129,3166 @@ pub async fn list_databases(pool: &PgPool) -> Result<Vec<String>, AppError> {
     )
     .fetch_all(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)?;
 
     let dbs = rows.iter().map(|row| row.get("datname")).collect();
     Ok(dbs)
 }
 
-/// List all tables, views, and functions in the database.
-pub async fn get_schema_objects(pool: &PgPool) -> Result<Vec<SchemaObject>, AppError> {
+/// Sizes of the databases `list_databases` returns, largest first. Databases the role
+/// lacks CONNECT privilege on are left out, since their size can't be read.
+pub async fn list_database_sizes(pool: &PgPool) -> Result<Vec<DatabaseSize>, AppError> {
     let rows = sqlx::query(
         r#"
-        SELECT table_name AS name, table_schema AS schema,
-               CASE table_type
-                   WHEN 'BASE TABLE' THEN 'table'
-                   WHEN 'VIEW' THEN 'view'
-               END AS object_type
-        FROM information_schema.tables
-        WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
-        ORDER BY table_schema, table_name
+        SELECT datname, pg_database_size(datname) AS bytes
+        FROM pg_database
+        WHERE datistemplate = false AND has_database_privilege(datname, 'CONNECT')
+        ORDER BY bytes DESC, datname
         "#,
     )
     .fetch_all(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)?;
 
-    let objects = rows
+    Ok(rows
         .iter()
-        .map(|row| {
-            let obj_type: String = row.get("object_type");
-            SchemaObject {
-                name: row.get("name"),
-                schema: row.get("schema"),
-                object_type: match obj_type.as_str() {
-                    "view" => SchemaObjectType::View,
-                    _ => SchemaObjectType::Table,
-                },
-            }
+        .map(|row| DatabaseSize {
+            name: row.get("datname"),
+            bytes: row.get("bytes"),
         })
-        .collect();
+        .collect())
+}
+
+/// Tables, views, materialized views, sequences, and functions outside the system schemas,
+/// as rows of (name, schema, object_type). Functions and procedures are listed once per
+/// name: overloads share a single entry, and functions installed by extensions are left out.
+const SCHEMA_OBJECTS_SQL: &str = r#"
+        SELECT * FROM (
+            SELECT table_name::text AS name, table_schema::text AS schema,
+                   CASE table_type
+                       WHEN 'BASE TABLE' THEN 'table'
+                       WHEN 'VIEW' THEN 'view'
+                   END AS object_type
+            FROM information_schema.tables
+            WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
+            UNION
+            SELECT matviewname::text AS name, schemaname::text AS schema,
+                   'materialized_view' AS object_type
+            FROM pg_matviews
+            WHERE schemaname NOT IN ('pg_catalog', 'information_schema')
+            UNION
+            SELECT sequencename::text AS name, schemaname::text AS schema, 'sequence' AS object_type
+            FROM pg_sequences
+            WHERE schemaname NOT IN ('pg_catalog', 'information_schema')
+            UNION
+            SELECT p.proname::text AS name, n.nspname::text AS schema, 'function' AS object_type
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND n.nspname NOT LIKE 'pg_toast%'
+              AND p.prokind IN ('f', 'p')
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_depend d
+                  WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e'
+              )
+        ) objects
+"#;
 
-    Ok(objects)
+/// Most results `search_schema_objects` returns.
+const MAX_SEARCH_RESULTS: i64 = 500;
+
+fn schema_object_from_row(row: &sqlx::postgres::PgRow) -> SchemaObject {
+    let obj_type: String = row.get("object_type");
+    SchemaObject {
+        name: row.get("name"),
+        schema: row.get("schema"),
+        object_type: match obj_type.as_str() {
+            "view" => SchemaObjectType::View,
+            "materialized_view" => SchemaObjectType::MaterializedView,
+            "function" => SchemaObjectType::Function,
+            "sequence" => SchemaObjectType::Sequence,
+            _ => SchemaObjectType::Table,
+        },
+    }
 }
 
-/// Get column info for a specific table.
-pub async fn get_columns(
+/// List all tables, views, materialized views, sequences, and functions in the database.
+/// Functions and procedures are listed once per name: overloads share a single entry,
+/// and functions installed by extensions are left out.
+/// With `schema_filter`, only objects in that schema are returned.
+pub async fn get_schema_objects(
     pool: &PgPool,
-    schema: &str,
-    table: &str,
-) -> Result<Vec<ColumnInfo>, AppError> {
+    schema_filter: Option<&str>,
+) -> Result<Vec<SchemaObject>, AppError> {
+    let sql = format!(
+        r#"{}
+        WHERE $1::text IS NULL OR schema = $1
+        ORDER BY schema, name
+        "#,
+        SCHEMA_OBJECTS_SQL
+    );
+    let rows = sqlx::query(&sql)
+        .bind(schema_filter)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from_sqlx)?;
+
+    Ok(rows.iter().map(schema_object_from_row).collect())
+}
+
+/// Find schema objects whose name contains `pattern`, ignoring case (`%` and `_` match
+/// literally). With `kinds` non-empty only those object types are returned. At most 500
+/// results, by schema and name.
+pub async fn search_schema_objects(
+    pool: &PgPool,
+    pattern: &str,
+    kinds: &[SchemaObjectType],
+) -> Result<Vec<SchemaObject>, AppError> {
+    let escaped = pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let kinds: Vec<&str> = kinds.iter().map(|k| k.as_str()).collect();
+    let sql = format!(
+        r#"{}
+        WHERE name ILIKE '%' || $1 || '%'
+          AND (cardinality($2::text[]) = 0 OR object_type = ANY($2))
+        ORDER BY schema, name
+        LIMIT $3
+        "#,
+        SCHEMA_OBJECTS_SQL
+    );
+    let rows = sqlx::query(&sql)
+        .bind(escaped)
+        .bind(kinds)
+        .bind(MAX_SEARCH_RESULTS)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from_sqlx)?;
+
+    Ok(rows.iter().map(schema_object_from_row).collect())
+}
+
+/// List enum types outside the system schemas with their values in declaration order.
+pub async fn list_enum_types(pool: &PgPool) -> Result<Vec<EnumType>, AppError> {
     let rows = sqlx::query(
         r#"
-        SELECT
-            c.column_name AS name,
-            c.data_type,
-            c.is_nullable = 'YES' AS is_nullable,
-            COALESCE(
-                (SELECT true FROM information_schema.key_column_usage kcu
-                 JOIN information_schema.table_constraints tc
-                   ON kcu.constraint_name = tc.constraint_name
-                  AND kcu.table_schema = tc.table_schema
-                 WHERE tc.constraint_type = 'PRIMARY KEY'
-                   AND kcu.table_schema = c.table_schema
-                   AND kcu.table_name = c.table_name
-                   AND kcu.column_name = c.column_name),
-                false
-            ) AS is_primary_key
-        FROM information_schema.columns c
-        WHERE c.table_schema = $1 AND c.table_name = $2
-        ORDER BY c.ordinal_position
+        SELECT n.nspname::text AS schema, t.typname::text AS name,
+               array_agg(e.enumlabel::text ORDER BY e.enumsortorder) AS values
+        FROM pg_type t
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        JOIN pg_enum e ON e.enumtypid = t.oid
+        WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+          AND n.nspname NOT LIKE 'pg_toast%'
+        GROUP BY n.nspname, t.typname
+        ORDER BY n.nspname, t.typname
         "#,
     )
-    .bind(schema)
-    .bind(table)
     .fetch_all(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)?;
 
-    let columns = rows
+    Ok(rows
         .iter()
-        .map(|row| ColumnInfo {
+        .map(|row| EnumType {
+            schema: row.get("schema"),
             name: row.get("name"),
-            data_type: row.get("data_type"),
-            is_nullable: row.get("is_nullable"),
-            is_primary_key: row.get("is_primary_key"),
+            values: row.get("values"),
         })
-        .collect();
+        .collect())
+}
 
-    Ok(columns)
+/// List the extensions installed in the database.
+pub async fn list_extensions(pool: &PgPool) -> Result<Vec<ExtensionInfo>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT e.extname::text AS name, e.extversion AS version, n.nspname::text AS schema
+        FROM pg_extension e
+        JOIN pg_namespace n ON n.oid = e.extnamespace
+        ORDER BY e.extname
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ExtensionInfo {
+            name: row.get("name"),
+            version: row.get("version"),
+            schema: row.get("schema"),
+        })
+        .collect())
 }
 
-/// Get the full DDL and structure info for a table.
-/// Returns: (columns, indexes, constraints, foreign_keys) as structured data.
-pub async fn get_table_structure(
-    pool: &PgPool,
-    schema: &str,
-    table: &str,
-) -> Result<crate::models::TableStructure, AppError> {
-    use crate::models::{ColumnDetail, IndexInfo, ConstraintInfo, ForeignKeyInfo};
+/// Install an extension with `CREATE EXTENSION IF NOT EXISTS`; already installed is fine.
+pub async fn create_extension(pool: &PgPool, name: &str) -> Result<(), AppError> {
+    if !is_valid_identifier(name) {
+        return Err(AppError::database("Invalid extension name".into()));
+    }
+    let sql = format!("CREATE EXTENSION IF NOT EXISTS {}", quote_ident(name));
+    sqlx::query(&sql)
+        .execute(pool)
+        .await
+        .map_err(|e| permission_error(e, &format!("create extension {}", name)))?;
+    Ok(())
+}
 
-    // 1. Detailed column info
-    let col_rows = sqlx::query(
+/// List schemas in the database, excluding system schemas.
+pub async fn list_schemas(pool: &PgPool) -> Result<Vec<String>, AppError> {
+    sqlx::query_scalar(
         r#"
-        SELECT
-            c.column_name,
-            c.data_type,
-            c.udt_name,
-            c.character_maximum_length,
-            c.numeric_precision,
-            c.numeric_scale,
-            c.is_nullable,
-            c.column_default
-        FROM information_schema.columns c
-        WHERE c.table_schema = $1 AND c.table_name = $2
-        ORDER BY c.ordinal_position
+        SELECT schema_name::text
+        FROM information_schema.schemata
+        WHERE schema_name NOT IN ('pg_catalog', 'information_schema')
+          AND schema_name NOT LIKE 'pg_toast%'
+          AND schema_name NOT LIKE 'pg_temp_%'
+        ORDER BY schema_name
         "#,
     )
-    .bind(schema)
-    .bind(table)
     .fetch_all(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)
+}
 
-    let columns: Vec<ColumnDetail> = col_rows
-        .iter()
-        .map(|row| {
-            let data_type: String = row.get("data_type");
-            let udt_name: String = row.get("udt_name");
-            let char_len: Option<i32> = row.get("character_maximum_length");
-            let num_prec: Option<i32> = row.get("numeric_precision");
-            let num_scale: Option<i32> = row.get("numeric_scale");
+/// Get the SQL definition of a view, materialized view, or function.
+/// Views return their pretty-printed SELECT; functions return the full
+/// CREATE OR REPLACE FUNCTION statement, one per overload separated by blank lines.
+pub async fn get_object_definition(
+    pool: &PgPool,
+    schema: &str,
+    name: &str,
+    object_type: &SchemaObjectType,
+) -> Result<String, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(name) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
 
-            // Build a display type like "varchar(255)" or "numeric(10,2)"
-            let display_type = if data_type == "character varying" {
-                match char_len {
-                    Some(l) => format!("varchar({})", l),
-                    None => "varchar".into(),
-                }
-            } else if data_type == "character" {
-                match char_len {
-                    Some(l) => format!("char({})", l),
-                    None => "char".into(),
-                }
-            } else if data_type == "numeric" {
-                match (num_prec, num_scale) {
-                    (Some(p), Some(s)) => format!("numeric({},{})", p, s),
-                    (Some(p), None) => format!("numeric({})", p),
-                    _ => "numeric".into(),
-                }
-            } else if data_type == "USER-DEFINED" {
-                udt_name.clone()
-            } else if data_type == "ARRAY" {
-                format!("{}[]", udt_name.trim_start_matches('_'))
-            } else {
-                data_type.clone()
-            };
+    let sql = match object_type {
+        SchemaObjectType::View | SchemaObjectType::MaterializedView => {
+            r#"
+            SELECT pg_get_viewdef(c.oid, true) AS definition
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = $1 AND c.relname = $2 AND c.relkind IN ('v', 'm')
+            "#
+        }
+        SchemaObjectType::Function => {
+            r#"
+            SELECT pg_get_functiondef(p.oid) AS definition
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            WHERE n.nspname = $1 AND p.proname = $2 AND p.prokind IN ('f', 'p')
+            ORDER BY p.oid
+            "#
+        }
+        _ => {
+            return Err(AppError::database(
+                "Definitions are only available for views and functions".into(),
+            ))
+        }
+    };
 
-            let nullable: String = row.get("is_nullable");
-            let default_val: Option<String> = row.get("column_default");
+    let rows = sqlx::query(sql)
+        .bind(schema)
+        .bind(name)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from_sqlx)?;
 
-            ColumnDetail {
-                name: row.get("column_name"),
-                data_type: display_type,
-                is_nullable: nullable == "YES",
-                default_value: default_val,
-            }
-        })
-        .collect();
+    if rows.is_empty() {
+        return Err(AppError::database(format!("{}.{} not found", schema, name)));
+    }
 
-    // 2. Indexes
-    let idx_rows = sqlx::query(
+    let definitions: Vec<String> = rows.iter().map(|row| row.get("definition")).collect();
+    Ok(definitions.join("\n\n"))
+}
+
+/// Get a sequence's parameters and last value.
+pub async fn get_sequence_info(
+    pool: &PgPool,
+    schema: &str,
+    name: &str,
+) -> Result<SequenceInfo, AppError> {
+    let row = sqlx::query(
         r#"
-        SELECT
-            i.relname AS index_name,
-            ix.indisunique AS is_unique,
-            ix.indisprimary AS is_primary,
-            pg_get_indexdef(ix.indexrelid) AS definition
-        FROM pg_index ix
-        JOIN pg_class t ON t.oid = ix.indrelid
-        JOIN pg_class i ON i.oid = ix.indexrelid
-        JOIN pg_namespace n ON n.oid = t.relnamespace
-        WHERE n.nspname = $1 AND t.relname = $2
-        ORDER BY i.relname
+        SELECT schemaname::text AS schema, sequencename::text AS name,
+               data_type::text AS data_type, start_value, increment_by,
+               min_value, max_value, last_value, cycle
+        FROM pg_sequences
+        WHERE schemaname = $1 AND sequencename = $2
         "#,
     )
     .bind(schema)
-    .bind(table)
-    .fetch_all(pool)
+    .bind(name)
+    .fetch_optional(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)?
+    .ok_or_else(|| AppError::database(format!("Sequence {}.{} not found", schema, name)))?;
 
-    let indexes: Vec<IndexInfo> = idx_rows
-        .iter()
-        .map(|row| IndexInfo {
-            name: row.get("index_name"),
-            is_unique: row.get("is_unique"),
-            is_primary: row.get("is_primary"),
-            definition: row.get("definition"),
-        })
-        .collect();
+    Ok(SequenceInfo {
+        schema: row.get("schema"),
+        name: row.get("name"),
+        data_type: row.get("data_type"),
+        start_value: row.get("start_value"),
+        increment: row.get("increment_by"),
+        min_value: row.get("min_value"),
+        max_value: row.get("max_value"),
+        last_value: row.get("last_value"),
+        is_cycled: row.get("cycle"),
+    })
+}
 
-    // 3. Constraints (CHECK, UNIQUE — excluding PKs and FKs which are shown separately)
-    let con_rows = sqlx::query(
+/// Planner's estimate of a table's row count from `pg_class.reltuples`. Cheap compared to
+/// COUNT(*), but only as fresh as the last VACUUM/ANALYZE; never-analyzed tables give 0.
+pub async fn get_table_row_estimate(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<i64, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+
+    sqlx::query_scalar(
         r#"
-        SELECT
-            con.conname AS name,
-            CASE con.contype
-                WHEN 'c' THEN 'CHECK'
-                WHEN 'u' THEN 'UNIQUE'
-                WHEN 'x' THEN 'EXCLUSION'
-            END AS constraint_type,
-            pg_get_constraintdef(con.oid) AS definition
-        FROM pg_constraint con
-        JOIN pg_class t ON t.oid = con.conrelid
-        JOIN pg_namespace n ON n.oid = t.relnamespace
-        WHERE n.nspname = $1 AND t.relname = $2
-          AND con.contype IN ('c', 'u', 'x')
-        ORDER BY con.conname
+        SELECT GREATEST(c.reltuples, 0)::bigint
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
         "#,
     )
     .bind(schema)
     .bind(table)
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)?
+    .ok_or_else(|| AppError::database(format!("Table {}.{} not found", schema, table)))
+}
 
-    let constraints: Vec<ConstraintInfo> = con_rows
-        .iter()
-        .map(|row| ConstraintInfo {
-            name: row.get("name"),
-            constraint_type: row.get("constraint_type"),
-            definition: row.get("definition"),
-        })
-        .collect();
+/// Get a table's size on disk: the whole relation, the table's main data, and its indexes.
+pub async fn get_table_size(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<TableSize, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
 
-    // 4. Foreign keys
-    let fk_rows = sqlx::query(
+    let row = sqlx::query(
         r#"
-        SELECT
-            con.conname AS name,
-            att.attname AS column_name,
-            ref_ns.nspname AS ref_schema,
-            ref_cl.relname AS ref_table,
-            ref_att.attname AS ref_column
-        FROM pg_constraint con
-        JOIN pg_class t ON t.oid = con.conrelid
-        JOIN pg_namespace n ON n.oid = t.relnamespace
-        JOIN pg_attribute att ON att.attrelid = con.conrelid AND att.attnum = ANY(con.conkey)
-        JOIN pg_class ref_cl ON ref_cl.oid = con.confrelid
-        JOIN pg_namespace ref_ns ON ref_ns.oid = ref_cl.relnamespace
-        JOIN pg_attribute ref_att ON ref_att.attrelid = con.confrelid AND ref_att.attnum = ANY(con.confkey)
-        WHERE n.nspname = $1 AND t.relname = $2 AND con.contype = 'f'
-        ORDER BY con.conname, att.attnum
+        SELECT pg_total_relation_size(c.oid) AS total_bytes,
+               pg_relation_size(c.oid) AS table_bytes,
+               pg_indexes_size(c.oid) AS index_bytes
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
         "#,
     )
     .bind(schema)
     .bind(table)
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    .map_err(AppError::from_sqlx)?
+    .ok_or_else(|| AppError::database(format!("Table {}.{} not found", schema, table)))?;
 
-    let foreign_keys: Vec<ForeignKeyInfo> = fk_rows
-        .iter()
-        .map(|row| ForeignKeyInfo {
-            name: row.get("name"),
-            column_name: row.get("column_name"),
-            ref_schema: row.get("ref_schema"),
-            ref_table: row.get("ref_table"),
-            ref_column: row.get("ref_column"),
+    Ok(TableSize {
+        total_bytes: row.get("total_bytes"),
+        table_bytes: row.get("table_bytes"),
+        index_bytes: row.get("index_bytes"),
+    })
+}
+
+/// Re-run a materialized view's query and replace its contents.
+pub async fn refresh_materialized_view(
+    pool: &PgPool,
+    schema: &str,
+    name: &str,
+) -> Result<(), AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(name) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    let sql = format!(
+        "REFRESH MATERIALIZED VIEW {}.{}",
+        quote_ident(schema),
+        quote_ident(name)
+    );
+    sqlx::query(&sql)
+        .execute(pool)
+        .await
+        .map_err(AppError::from_sqlx)?;
+    Ok(())
+}
+
+/// Run VACUUM, VACUUM FULL, ANALYZE or REINDEX on a table. These can't run inside a
+/// transaction block, so the statement is sent on its own over the simple protocol on a
+/// pooled connection, which is in autocommit.
+pub async fn maintain_table(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    op: MaintenanceOp,
+) -> Result<(), AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    let sql = format!(
+        "{} {}.{}",
+        op.as_sql(),
+        quote_ident(schema),
+        quote_ident(table)
+    );
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+    sqlx::raw_sql(&sql)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| permission_error(e, &format!("{} {}.{}", op.as_sql(), schema, table)))?;
+    Ok(())
+}
+
+/// Get the current user's role attributes and every role it is a member of,
+/// following pg_auth_members transitively.
+pub async fn get_current_role_info(pool: &PgPool) -> Result<RoleInfo, AppError> {
+    let me = sqlx::query(
+        r#"
+        SELECT current_user::text AS current_name,
+               session_user::text AS session_name,
+               r.rolsuper, r.rolcreaterole, r.rolcreatedb
+        FROM pg_roles r
+        WHERE r.rolname = current_user
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let member_rows = sqlx::query(
+        r#"
+        WITH RECURSIVE memberships AS (
+            SELECT m.roleid, true AS is_direct
+            FROM pg_auth_members m
+            JOIN pg_roles me ON me.oid = m.member
+            WHERE me.rolname = current_user
+            UNION
+            SELECT m.roleid, false
+            FROM pg_auth_members m
+            JOIN memberships ms ON m.member = ms.roleid
+        )
+        SELECT r.rolname AS name,
+               bool_or(ms.is_direct) AS is_direct,
+               r.rolsuper, r.rolcreaterole, r.rolcreatedb
+        FROM memberships ms
+        JOIN pg_roles r ON r.oid = ms.roleid
+        GROUP BY r.rolname, r.rolsuper, r.rolcreaterole, r.rolcreatedb
+        ORDER BY r.rolname
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let member_of = member_rows
+        .iter()
+        .map(|row| RoleMembership {
+            name: row.get("name"),
+            is_direct: row.get("is_direct"),
+            is_superuser: row.get("rolsuper"),
+            can_create_role: row.get("rolcreaterole"),
+            can_create_db: row.get("rolcreatedb"),
         })
         .collect();
 
-    Ok(crate::models::TableStructure {
-        columns,
-        indexes,
-        constraints,
-        foreign_keys,
+    Ok(RoleInfo {
+        current_user: me.get("current_name"),
+        session_user: me.get("session_name"),
+        is_superuser: me.get("rolsuper"),
+        can_create_role: me.get("rolcreaterole"),
+        can_create_db: me.get("rolcreatedb"),
+        member_of,
+    })
+}
+
+/// Get the server version and key settings.
+pub async fn get_server_info(pool: &PgPool) -> Result<ServerInfo, AppError> {
+    let row = sqlx::query(
+        r#"
+        SELECT version() AS version,
+               current_setting('server_version_num')::int AS version_num,
+               current_setting('server_encoding') AS encoding,
+               current_setting('TimeZone') AS timezone,
+               current_setting('max_connections')::int AS max_connections
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    Ok(ServerInfo {
+        version: row.get("version"),
+        version_num: row.get("version_num"),
+        encoding: row.get("encoding"),
+        timezone: row.get("timezone"),
+        max_connections: row.get("max_connections"),
     })
 }
 
-/// Get primary key column names for a table, in constraint order.
-/// Returns empty vec if the table has no primary key.
-pub async fn get_primary_key_columns(
+/// List client backends from pg_stat_activity, optionally only those on the current
+/// database.
+pub async fn list_active_sessions(
+    pool: &PgPool,
+    current_database_only: bool,
+) -> Result<Vec<SessionInfo>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT pid, usename::text, datname::text, application_name, client_addr::text,
+               state, query, query_start, pid = pg_backend_pid() AS is_current
+        FROM pg_stat_activity
+        WHERE backend_type = 'client backend'
+          AND (NOT $1 OR datname = current_database())
+        ORDER BY query_start DESC NULLS LAST, pid
+        "#,
+    )
+    .bind(current_database_only)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| permission_error(e, "list sessions (requires pg_monitor or superuser)"))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| SessionInfo {
+            pid: row.get("pid"),
+            usename: row.get("usename"),
+            datname: row.get("datname"),
+            application_name: row.get("application_name"),
+            client_addr: row.get("client_addr"),
+            state: row.get("state"),
+            query: row.get("query"),
+            query_start: row
+                .get::<Option<chrono::DateTime<chrono::Utc>>, _>("query_start")
+                .map(|t| t.to_rfc3339()),
+            is_current: row.get("is_current"),
+        })
+        .collect())
+}
+
+/// List sessions waiting on locks, one entry per (blocked, blocking) pair, using
+/// pg_blocking_pids joined back to pg_stat_activity, and pg_locks for the awaited lock.
+pub async fn list_blocking_locks(pool: &PgPool) -> Result<Vec<LockInfo>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT blocked.pid AS blocked_pid, blocked.usename::text AS blocked_user,
+               blocked.query AS blocked_query, blocked.query_start AS blocked_since,
+               blocking.pid AS blocking_pid, blocking.usename::text AS blocking_user,
+               blocking.query AS blocking_query, blocking.state AS blocking_state,
+               waiting.locktype AS lock_type, waiting.mode AS lock_mode,
+               waiting.relation::regclass::text AS relation
+        FROM pg_stat_activity blocked
+        CROSS JOIN LATERAL unnest(pg_blocking_pids(blocked.pid)) AS b(pid)
+        JOIN pg_stat_activity blocking ON blocking.pid = b.pid
+        LEFT JOIN pg_locks waiting ON waiting.pid = blocked.pid AND NOT waiting.granted
+        ORDER BY blocked.query_start NULLS LAST, blocked.pid, blocking.pid
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| permission_error(e, "list locks (requires pg_monitor or superuser)"))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| LockInfo {
+            blocked_pid: row.get("blocked_pid"),
+            blocked_user: row.get("blocked_user"),
+            blocked_query: row.get("blocked_query"),
+            blocked_since: row
+                .get::<Option<chrono::DateTime<chrono::Utc>>, _>("blocked_since")
+                .map(|t| t.to_rfc3339()),
+            blocking_pid: row.get("blocking_pid"),
+            blocking_user: row.get("blocking_user"),
+            blocking_query: row.get("blocking_query"),
+            blocking_state: row.get("blocking_state"),
+            lock_type: row.get("lock_type"),
+            lock_mode: row.get("lock_mode"),
+            relation: row.get("relation"),
+        })
+        .collect())
+}
+
+/// Signal a backend: pg_terminate_backend when `terminate`, otherwise pg_cancel_backend
+/// (which only cancels its running query). Returns false if no such backend existed
+/// (or it had already exited).
+pub async fn signal_backend(pool: &PgPool, pid: i32, terminate: bool) -> Result<bool, AppError> {
+    if pid <= 0 {
+        return Err(AppError::database(format!("Invalid backend pid: {}", pid)));
+    }
+    let (sql, action) = if terminate {
+        (
+            "SELECT pg_terminate_backend($1)",
+            "terminate the backend (requires pg_signal_backend or superuser)",
+        )
+    } else {
+        (
+            "SELECT pg_cancel_backend($1)",
+            "cancel the backend's query (requires pg_signal_backend or superuser)",
+        )
+    };
+    sqlx::query_scalar(sql)
+        .bind(pid)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| permission_error(e, action))
+}
+
+/// Terminate every other backend connected to `database`, e.g. before dropping or
+/// renaming it. Returns how many were terminated. Sessions of other roles are only
+/// terminated with pg_signal_backend or superuser; without it the call fails with 42501.
+pub async fn terminate_database_connections(
+    pool: &PgPool,
+    database: &str,
+) -> Result<u64, AppError> {
+    let terminated: i64 = sqlx::query_scalar(
+        "SELECT count(*) FILTER (WHERE pg_terminate_backend(pid)) \
+         FROM pg_stat_activity \
+         WHERE datname = $1 AND pid <> pg_backend_pid()",
+    )
+    .bind(database)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        permission_error(
+            e,
+            "terminate connections (requires pg_signal_backend or superuser)",
+        )
+    })?;
+    Ok(terminated as u64)
+}
+
+/// Turn a 42501 insufficient_privilege error into a message naming what was attempted;
+/// other errors pass through unchanged.
+fn permission_error(e: sqlx::Error, action: &str) -> AppError {
+    match AppError::from_sqlx(e) {
+        AppError::Database {
+            code: Some(code), ..
+        } if code == "42501" => AppError::Database {
+            message: format!("Permission denied: cannot {}", action),
+            code: Some(code),
+            position: None,
+        },
+        other => other,
+    }
+}
+
+/// Get column info for a specific table.
+pub async fn get_columns<'c, E>(
+    executor: E,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<ColumnInfo>, AppError>
+where
+    E: Executor<'c, Database = sqlx::Postgres>,
+{
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            c.column_name AS name,
+            c.data_type,
+            c.is_nullable = 'YES' AS is_nullable,
+            COALESCE(
+                (SELECT true FROM information_schema.key_column_usage kcu
+                 JOIN information_schema.table_constraints tc
+                   ON kcu.constraint_name = tc.constraint_name
+                  AND kcu.table_schema = tc.table_schema
+                 WHERE tc.constraint_type = 'PRIMARY KEY'
+                   AND kcu.table_schema = c.table_schema
+                   AND kcu.table_name = c.table_name
+                   AND kcu.column_name = c.column_name),
+                false
+            ) AS is_primary_key,
+            c.is_generated = 'ALWAYS' AS is_generated
+        FROM information_schema.columns c
+        WHERE c.table_schema = $1 AND c.table_name = $2
+        ORDER BY c.ordinal_position
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let columns = rows
+        .iter()
+        .map(|row| ColumnInfo {
+            name: row.get("name"),
+            data_type: row.get("data_type"),
+            is_nullable: row.get("is_nullable"),
+            is_primary_key: row.get("is_primary_key"),
+            is_generated: row.get("is_generated"),
+        })
+        .collect();
+
+    Ok(columns)
+}
+
+/// Get column info for many tables in one query, keyed by "schema.table". Every requested
+/// table gets an entry, empty if it has no columns or doesn't exist.
+pub async fn get_columns_bulk(
+    pool: &PgPool,
+    tables: &[(String, String)],
+) -> Result<HashMap<String, Vec<ColumnInfo>>, AppError> {
+    if tables
+        .iter()
+        .any(|(schema, table)| !is_valid_identifier(schema) || !is_valid_identifier(table))
+    {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    let mut columns: HashMap<String, Vec<ColumnInfo>> = tables
+        .iter()
+        .map(|(schema, table)| (format!("{}.{}", schema, table), Vec::new()))
+        .collect();
+    if tables.is_empty() {
+        return Ok(columns);
+    }
+
+    let (schemas, names): (Vec<&str>, Vec<&str>) = tables
+        .iter()
+        .map(|(schema, table)| (schema.as_str(), table.as_str()))
+        .unzip();
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            c.table_schema::text,
+            c.table_name::text,
+            c.column_name AS name,
+            c.data_type,
+            c.is_nullable = 'YES' AS is_nullable,
+            COALESCE(
+                (SELECT true FROM information_schema.key_column_usage kcu
+                 JOIN information_schema.table_constraints tc
+                   ON kcu.constraint_name = tc.constraint_name
+                  AND kcu.table_schema = tc.table_schema
+                 WHERE tc.constraint_type = 'PRIMARY KEY'
+                   AND kcu.table_schema = c.table_schema
+                   AND kcu.table_name = c.table_name
+                   AND kcu.column_name = c.column_name),
+                false
+            ) AS is_primary_key,
+            c.is_generated = 'ALWAYS' AS is_generated
+        FROM information_schema.columns c
+        WHERE (c.table_schema, c.table_name) IN (
+            SELECT * FROM unnest($1::text[], $2::text[])
+        )
+        ORDER BY c.table_schema, c.table_name, c.ordinal_position
+        "#,
+    )
+    .bind(schemas)
+    .bind(names)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    for row in &rows {
+        let schema: String = row.get("table_schema");
+        let table: String = row.get("table_name");
+        columns
+            .entry(format!("{}.{}", schema, table))
+            .or_default()
+            .push(ColumnInfo {
+                name: row.get("name"),
+                data_type: row.get("data_type"),
+                is_nullable: row.get("is_nullable"),
+                is_primary_key: row.get("is_primary_key"),
+                is_generated: row.get("is_generated"),
+            });
+    }
+    Ok(columns)
+}
+
+/// Get the full DDL and structure info for a table.
+/// Returns: (columns, indexes, constraints, foreign_keys) as structured data.
+pub async fn get_table_structure(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<crate::models::TableStructure, AppError> {
+    use crate::models::{ColumnDetail, IndexInfo, ConstraintInfo, ForeignKeyInfo, TriggerInfo};
+
+    // 1. Detailed column info
+    let col_rows = sqlx::query(
+        r#"
+        SELECT
+            c.column_name,
+            c.data_type,
+            c.udt_name,
+            c.character_maximum_length,
+            c.numeric_precision,
+            c.numeric_scale,
+            c.is_nullable,
+            c.column_default,
+            c.identity_generation::text,
+            c.generation_expression::text,
+            c.is_generated = 'ALWAYS' AS is_generated,
+            NULLIF(col_description(
+                format('%I.%I', c.table_schema, c.table_name)::regclass,
+                c.ordinal_position::int
+            ), '') AS comment
+        FROM information_schema.columns c
+        WHERE c.table_schema = $1 AND c.table_name = $2
+        ORDER BY c.ordinal_position
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let columns: Vec<ColumnDetail> = col_rows
+        .iter()
+        .map(|row| {
+            let data_type: String = row.get("data_type");
+            let udt_name: String = row.get("udt_name");
+            let char_len: Option<i32> = row.get("character_maximum_length");
+            let num_prec: Option<i32> = row.get("numeric_precision");
+            let num_scale: Option<i32> = row.get("numeric_scale");
+
+            // Build a display type like "varchar(255)" or "numeric(10,2)"
+            let display_type = if data_type == "character varying" {
+                match char_len {
+                    Some(l) => format!("varchar({})", l),
+                    None => "varchar".into(),
+                }
+            } else if data_type == "character" {
+                match char_len {
+                    Some(l) => format!("char({})", l),
+                    None => "char".into(),
+                }
+            } else if data_type == "numeric" {
+                match (num_prec, num_scale) {
+                    (Some(p), Some(s)) => format!("numeric({},{})", p, s),
+                    (Some(p), None) => format!("numeric({})", p),
+                    _ => "numeric".into(),
+                }
+            } else if data_type == "USER-DEFINED" {
+                udt_name.clone()
+            } else if data_type == "ARRAY" {
+                format!("{}[]", udt_name.trim_start_matches('_'))
+            } else {
+                data_type.clone()
+            };
+
+            let nullable: String = row.get("is_nullable");
+            let raw_default: Option<String> = row.get("column_default");
+            let identity_generation: Option<String> = row.get("identity_generation");
+            let uses_sequence = raw_default
+                .as_deref()
+                .is_some_and(|d| d.contains("nextval("));
+
+            ColumnDetail {
+                name: row.get("column_name"),
+                data_type: display_type,
+                is_nullable: nullable == "YES",
+                default_value: raw_default.clone().filter(|_| !uses_sequence),
+                raw_default,
+                is_auto_increment: uses_sequence || identity_generation.is_some(),
+                comment: row.get("comment"),
+                identity_generation,
+                generation_expression: row.get("generation_expression"),
+                is_generated: row.get("is_generated"),
+            }
+        })
+        .collect();
+
+    // 2. Indexes
+    let idx_rows = sqlx::query(
+        r#"
+        SELECT
+            i.relname AS index_name,
+            ix.indisunique AS is_unique,
+            ix.indisprimary AS is_primary,
+            pg_get_indexdef(ix.indexrelid) AS definition
+        FROM pg_index ix
+        JOIN pg_class t ON t.oid = ix.indrelid
+        JOIN pg_class i ON i.oid = ix.indexrelid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        WHERE n.nspname = $1 AND t.relname = $2
+        ORDER BY i.relname
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let indexes: Vec<IndexInfo> = idx_rows
+        .iter()
+        .map(|row| IndexInfo {
+            name: row.get("index_name"),
+            is_unique: row.get("is_unique"),
+            is_primary: row.get("is_primary"),
+            definition: row.get("definition"),
+        })
+        .collect();
+
+    // 3. Constraints (CHECK, UNIQUE — excluding PKs and FKs which are shown separately)
+    let con_rows = sqlx::query(
+        r#"
+        SELECT
+            con.conname AS name,
+            CASE con.contype
+                WHEN 'c' THEN 'CHECK'
+                WHEN 'u' THEN 'UNIQUE'
+                WHEN 'x' THEN 'EXCLUSION'
+            END AS constraint_type,
+            pg_get_constraintdef(con.oid) AS definition
+        FROM pg_constraint con
+        JOIN pg_class t ON t.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        WHERE n.nspname = $1 AND t.relname = $2
+          AND con.contype IN ('c', 'u', 'x')
+        ORDER BY con.conname
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let constraints: Vec<ConstraintInfo> = con_rows
+        .iter()
+        .map(|row| ConstraintInfo {
+            name: row.get("name"),
+            constraint_type: row.get("constraint_type"),
+            definition: row.get("definition"),
+        })
+        .collect();
+
+    // 4. Foreign keys
+    let fk_rows = sqlx::query(
+        r#"
+        SELECT
+            con.conname AS name,
+            att.attname AS column_name,
+            ref_ns.nspname AS ref_schema,
+            ref_cl.relname AS ref_table,
+            ref_att.attname AS ref_column
+        FROM pg_constraint con
+        JOIN pg_class t ON t.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        JOIN pg_attribute att ON att.attrelid = con.conrelid AND att.attnum = ANY(con.conkey)
+        JOIN pg_class ref_cl ON ref_cl.oid = con.confrelid
+        JOIN pg_namespace ref_ns ON ref_ns.oid = ref_cl.relnamespace
+        JOIN pg_attribute ref_att ON ref_att.attrelid = con.confrelid AND ref_att.attnum = ANY(con.confkey)
+        WHERE n.nspname = $1 AND t.relname = $2 AND con.contype = 'f'
+        ORDER BY con.conname, att.attnum
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let foreign_keys: Vec<ForeignKeyInfo> = fk_rows
+        .iter()
+        .map(|row| ForeignKeyInfo {
+            name: row.get("name"),
+            column_name: row.get("column_name"),
+            ref_schema: row.get("ref_schema"),
+            ref_table: row.get("ref_table"),
+            ref_column: row.get("ref_column"),
+        })
+        .collect();
+
+    // 5. Triggers (user-defined; internal ones backing FKs/constraints are skipped)
+    let trigger_rows = sqlx::query(
+        r#"
+        SELECT
+            tg.tgname::text AS name,
+            CASE
+                WHEN tg.tgtype & 2 <> 0 THEN 'BEFORE'
+                WHEN tg.tgtype & 64 <> 0 THEN 'INSTEAD OF'
+                ELSE 'AFTER'
+            END AS timing,
+            array_remove(ARRAY[
+                CASE WHEN tg.tgtype & 4 <> 0 THEN 'INSERT' END,
+                CASE WHEN tg.tgtype & 16 <> 0 THEN 'UPDATE' END,
+                CASE WHEN tg.tgtype & 8 <> 0 THEN 'DELETE' END,
+                CASE WHEN tg.tgtype & 32 <> 0 THEN 'TRUNCATE' END
+            ], NULL) AS events,
+            pg_get_triggerdef(tg.oid, true) AS definition
+        FROM pg_trigger tg
+        JOIN pg_class t ON t.oid = tg.tgrelid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        WHERE n.nspname = $1 AND t.relname = $2 AND NOT tg.tgisinternal
+        ORDER BY tg.tgname
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    let triggers: Vec<TriggerInfo> = trigger_rows
+        .iter()
+        .map(|row| TriggerInfo {
+            name: row.get("name"),
+            timing: row.get("timing"),
+            events: row.get("events"),
+            definition: row.get("definition"),
+        })
+        .collect();
+
+    // 6. Table comment
+    let table_comment: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT NULLIF(obj_description(c.oid, 'pg_class'), '')
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1 AND c.relname = $2
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::from_sqlx)?
+    .flatten();
+
+    Ok(crate::models::TableStructure {
+        columns,
+        indexes,
+        constraints,
+        foreign_keys,
+        triggers,
+        table_comment,
+    })
+}
+
+/// Get primary key column names for a table, in constraint order.
+/// Returns empty vec if the table has no primary key.
+pub async fn get_primary_key_columns(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<String>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT kcu.column_name
+        FROM information_schema.table_constraints tc
+        JOIN information_schema.key_column_usage kcu
+          ON tc.constraint_name = kcu.constraint_name
+         AND tc.table_schema = kcu.table_schema
+         AND tc.table_name = kcu.table_name
+        WHERE tc.constraint_type = 'PRIMARY KEY'
+          AND tc.table_schema = $1 AND tc.table_name = $2
+        ORDER BY kcu.ordinal_position
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    Ok(rows.iter().map(|r| r.get("column_name")).collect())
+}
+
+/// Validate that a string is a safe PostgreSQL identifier (for schema, table, column).
+/// Identifiers are always emitted through `quote_ident`, so any name Postgres accepts is
+/// allowed (spaces, mixed case, Unicode, embedded quotes); only empty names and NUL bytes
+/// are rejected.
+pub(crate) fn is_valid_identifier(s: &str) -> bool {
+    !s.is_empty() && !s.contains('\0')
+}
+
+/// Quote an identifier for SQL: wrap it in double quotes, doubling any embedded ones.
+pub(crate) fn quote_ident(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Update a single cell value. Uses parameterized queries for values; validates identifiers.
+/// `new_value` of None (or JSON null) sets the cell to SQL NULL.
+/// Values are converted to the column's real type before binding and every placeholder is
+/// cast to the column type, so jsonb/uuid/timestamptz/etc. columns don't rely on text coercion.
+pub async fn update_cell(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    column: &str,
+    primary_key_columns: &[String],
+    primary_key_values: &[serde_json::Value],
+    new_value: Option<&serde_json::Value>,
+) -> Result<u64, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) || !is_valid_identifier(column) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    if primary_key_columns.is_empty() {
+        return Err(AppError::database("Table has no primary key; cannot update".into()));
+    }
+    if primary_key_columns.len() != primary_key_values.len() {
+        return Err(AppError::database("Primary key column/value count mismatch".into()));
+    }
+    for pk_col in primary_key_columns {
+        if !is_valid_identifier(pk_col) {
+            return Err(AppError::database("Invalid primary key column name".into()));
+        }
+    }
+
+    let column_types = get_column_types(&mut *conn, schema, table).await?;
+    let type_of = |name: &str| {
+        column_types
+            .get(name)
+            .ok_or_else(|| AppError::database(format!("Unknown column: {}", name)))
+    };
+
+    if type_of(column)?.is_generated {
+        return Err(generated_column_error(column));
+    }
+
+    // Build: UPDATE "schema"."table" SET "column" = $1::type WHERE "pk1" = $2::type ...
+    let set_clause = format!("{} = $1::{}", quote_ident(column), type_of(column)?.formatted);
+    let mut where_parts = Vec::with_capacity(primary_key_columns.len());
+    for (i, c) in primary_key_columns.iter().enumerate() {
+        where_parts.push(format!("{} = ${}::{}", quote_ident(c), i + 2, type_of(c)?.formatted));
+    }
+    let where_clause = where_parts.join(" AND ");
+    let sql = format!(
+        "UPDATE {}.{} SET {} WHERE {}",
+        quote_ident(schema),
+        quote_ident(table),
+        set_clause,
+        where_clause
+    );
+
+    let mut q = json_to_param(new_value, type_of(column)?)?.bind_to(sqlx::query(&sql));
+
+    for (c, v) in primary_key_columns.iter().zip(primary_key_values) {
+        q = json_to_param(Some(v), type_of(c)?)?.bind_to(q);
+    }
+
+    let result = q.execute(&mut *conn).await.map_err(AppError::from_sqlx)?;
+    Ok(result.rows_affected())
+}
+
+/// Apply several cell updates atomically: they run in one transaction (a savepoint when
+/// `conn` is already inside one) that is rolled back if any edit fails.
+/// Returns the total number of rows updated.
+pub async fn update_cells(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    edits: &[CellEdit],
+) -> Result<u64, AppError> {
+    let mut tx = conn.begin().await.map_err(AppError::from_sqlx)?;
+
+    let mut rows_affected = 0;
+    for edit in edits {
+        let result = update_cell(
+            &mut tx,
+            schema,
+            table,
+            &edit.column,
+            &edit.primary_key_columns,
+            &edit.primary_key_values,
+            Some(&edit.new_value),
+        )
+        .await;
+        match result {
+            Ok(n) => rows_affected += n,
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return Err(e);
+            }
+        }
+    }
+
+    tx.commit().await.map_err(AppError::from_sqlx)?;
+    Ok(rows_affected)
+}
+
+/// Refusal to write a generated column, given before the server would reject it with a
+/// less obvious error.
+fn generated_column_error(column: &str) -> AppError {
+    AppError::database(format!("column {} is generated; cannot write", column))
+}
+
+/// Server-side type of a column: the full `format_type` spelling (usable as a cast
+/// target), the base type name (domains resolved), and for arrays the element type name.
+/// `is_generated` marks generated columns, which can't be written to, and
+/// `is_auto_increment` identity columns and columns defaulting to a sequence.
+struct ColumnType {
+    formatted: String,
+    type_name: String,
+    element_type: Option<String>,
+    is_generated: bool,
+    is_auto_increment: bool,
+}
+
+/// Look up the server-side types of every column of a table, keyed by column name.
+async fn get_column_types(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+) -> Result<HashMap<String, ColumnType>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT a.attname::text AS name,
+               format_type(a.atttypid, a.atttypmod) AS formatted,
+               COALESCE(bt.typname, t.typname)::text AS type_name,
+               et.typname::text AS element_type,
+               a.attgenerated <> '' AS is_generated,
+               a.attidentity <> ''
+                   OR COALESCE(pg_get_expr(d.adbin, d.adrelid) LIKE '%nextval(%', false)
+                   AS is_auto_increment
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_type t ON t.oid = a.atttypid
+        LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+        LEFT JOIN pg_type bt ON bt.oid = t.typbasetype AND t.typtype = 'd'
+        LEFT JOIN pg_type et ON et.oid = COALESCE(bt.typelem, t.typelem)
+                            AND COALESCE(bt.typcategory, t.typcategory) = 'A'
+        WHERE n.nspname = $1 AND c.relname = $2
+          AND a.attnum > 0 AND NOT a.attisdropped
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::from_sqlx)?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            (
+                row.get("name"),
+                ColumnType {
+                    formatted: row.get("formatted"),
+                    type_name: row.get("type_name"),
+                    element_type: row.get("element_type"),
+                    is_generated: row.get("is_generated"),
+                    is_auto_increment: row.get("is_auto_increment"),
+                },
+            )
+        })
+        .collect())
+}
+
+/// A value converted for binding against a particular column type.
+enum SqlParam {
+    Text(Option<String>),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Uuid(uuid::Uuid),
+    Timestamptz(chrono::DateTime<chrono::Utc>),
+    Json(serde_json::Value),
+    Bytes(Vec<u8>),
+    BoolArray(Vec<Option<bool>>),
+    IntArray(Vec<Option<i64>>),
+    FloatArray(Vec<Option<f64>>),
+    TextArray(Vec<Option<String>>),
+}
+
+impl SqlParam {
+    fn bind_to<'q>(self, q: PgQuery<'q>) -> PgQuery<'q> {
+        match self {
+            SqlParam::Text(v) => q.bind(v),
+            SqlParam::Bool(v) => q.bind(v),
+            SqlParam::Int(v) => q.bind(v),
+            SqlParam::Float(v) => q.bind(v),
+            SqlParam::Uuid(v) => q.bind(v),
+            SqlParam::Timestamptz(v) => q.bind(v),
+            SqlParam::Json(v) => q.bind(v),
+            SqlParam::Bytes(v) => q.bind(v),
+            SqlParam::BoolArray(v) => q.bind(v),
+            SqlParam::IntArray(v) => q.bind(v),
+            SqlParam::FloatArray(v) => q.bind(v),
+            SqlParam::TextArray(v) => q.bind(v),
+        }
+    }
+}
+
+/// Convert a JSON value into a parameter of the column's type. Values that can't be
+/// coerced are rejected with a readable error before anything is sent to the server.
+/// Types without a dedicated conversion are bound as text for the placeholder cast.
+fn json_to_param(
+    value: Option<&serde_json::Value>,
+    column: &ColumnType,
+) -> Result<SqlParam, AppError> {
+    use serde_json::Value;
+
+    let value = match value {
+        None | Some(Value::Null) => return Ok(SqlParam::Text(None)),
+        Some(v) => v,
+    };
+    let mismatch = || {
+        AppError::database(format!(
+            "Value {} cannot be stored in a {} column",
+            value, column.formatted
+        ))
+    };
+
+    if let Some(element_type) = &column.element_type {
+        return match value {
+            Value::Array(items) => json_array_to_param(items, element_type),
+            // Array literals like '{1,2}' are parsed by the server.
+            Value::String(s) => Ok(SqlParam::Text(Some(s.clone()))),
+            _ => Err(mismatch()),
+        };
+    }
+
+    let param = match (column.type_name.as_str(), value) {
+        ("int2" | "int4" | "int8", Value::Number(n)) => {
+            SqlParam::Int(n.as_i64().ok_or_else(mismatch)?)
+        }
+        ("int2" | "int4" | "int8", Value::String(s)) => {
+            SqlParam::Int(s.trim().parse().map_err(|_| mismatch())?)
+        }
+        ("float4" | "float8", Value::Number(n)) => {
+            SqlParam::Float(n.as_f64().ok_or_else(mismatch)?)
+        }
+        ("float4" | "float8", Value::String(s)) => {
+            SqlParam::Float(s.trim().parse().map_err(|_| mismatch())?)
+        }
+        ("bool", Value::Bool(b)) => SqlParam::Bool(*b),
+        ("bool", Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" | "t" | "yes" | "on" | "1" => SqlParam::Bool(true),
+            "false" | "f" | "no" | "off" | "0" => SqlParam::Bool(false),
+            _ => return Err(mismatch()),
+        },
+        ("uuid", Value::String(s)) => SqlParam::Uuid(s.trim().parse().map_err(|_| mismatch())?),
+        // Strings typed into a json cell are JSON text, not a JSON string value.
+        ("json" | "jsonb", Value::String(s)) => {
+            SqlParam::Json(serde_json::from_str(s).map_err(|_| mismatch())?)
+        }
+        ("json" | "jsonb", other) => SqlParam::Json(other.clone()),
+        // Other spellings ('2024-01-01 10:00+02', 'now') are left to the server to parse.
+        ("timestamptz", Value::String(s)) => {
+            match chrono::DateTime::parse_from_rfc3339(s.trim()) {
+                Ok(ts) => SqlParam::Timestamptz(ts.with_timezone(&chrono::Utc)),
+                Err(_) => SqlParam::Text(Some(s.clone())),
+            }
+        }
+        // The tagged form produced when reading bytea; plain strings use bytea input syntax.
+        ("bytea", Value::Object(obj)) => {
+            use base64::Engine;
+            let encoded = obj
+                .get("base64")
+                .and_then(Value::as_str)
+                .ok_or_else(mismatch)?;
+            SqlParam::Bytes(
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .map_err(|_| mismatch())?,
+            )
+        }
+        (
+            "int2" | "int4" | "int8" | "float4" | "float8" | "bool" | "uuid" | "timestamptz"
+            | "bytea",
+            _,
+        ) => return Err(mismatch()),
+        (_, Value::Array(_) | Value::Object(_)) => return Err(mismatch()),
+        (_, other) => SqlParam::Text(serde_json_value_to_sql(other)),
+    };
+    Ok(param)
+}
+
+/// Convert a JSON array into a Postgres array parameter for the given element type
+/// (pg_type.typname, e.g. `int4`). Numeric and boolean elements are bound natively;
+/// anything else is bound as text[] and cast by the server.
+fn json_array_to_param(
+    items: &[serde_json::Value],
+    element_type: &str,
+) -> Result<SqlParam, AppError> {
+    let bad_element = |v: &serde_json::Value| {
+        AppError::database(format!(
+            "Array element {} is not a valid {}",
+            v, element_type
+        ))
+    };
+
+    let param = match element_type {
+        "int2" | "int4" | "int8" => SqlParam::IntArray(
+            items
+                .iter()
+                .map(|v| match v {
+                    serde_json::Value::Null => Ok(None),
+                    serde_json::Value::Number(n) => {
+                        n.as_i64().map(Some).ok_or_else(|| bad_element(v))
+                    }
+                    serde_json::Value::String(s) => {
+                        s.trim().parse().map(Some).map_err(|_| bad_element(v))
+                    }
+                    _ => Err(bad_element(v)),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        "float4" | "float8" => SqlParam::FloatArray(
+            items
+                .iter()
+                .map(|v| match v {
+                    serde_json::Value::Null => Ok(None),
+                    serde_json::Value::Number(n) => {
+                        n.as_f64().map(Some).ok_or_else(|| bad_element(v))
+                    }
+                    serde_json::Value::String(s) => {
+                        s.trim().parse().map(Some).map_err(|_| bad_element(v))
+                    }
+                    _ => Err(bad_element(v)),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        "bool" => SqlParam::BoolArray(
+            items
+                .iter()
+                .map(|v| match v {
+                    serde_json::Value::Null => Ok(None),
+                    serde_json::Value::Bool(b) => Ok(Some(*b)),
+                    _ => Err(bad_element(v)),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => SqlParam::TextArray(items.iter().map(serde_json_value_to_sql).collect()),
+    };
+    Ok(param)
+}
+
+/// Insert a new row and return it as stored (via RETURNING *), so generated ids and
+/// defaults are visible. Columns missing from `values` get their DEFAULT; a present
+/// None (JSON null) inserts NULL. Values are converted to each column's real type and
+/// every placeholder is cast to it, as in `update_cell`.
+pub async fn insert_row(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    values: &HashMap<String, Option<serde_json::Value>>,
+) -> Result<QueryResult, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    // Sorted so the same set of columns always produces the same statement.
+    let mut columns: Vec<&String> = values.keys().collect();
+    columns.sort();
+    for col in &columns {
+        if !is_valid_identifier(col) {
+            return Err(AppError::database("Invalid column name".into()));
+        }
+    }
+
+    if columns.is_empty() {
+        let sql = format!(
+            "INSERT INTO {}.{} DEFAULT VALUES RETURNING *",
+            quote_ident(schema),
+            quote_ident(table)
+        );
+        return fetch_result(&mut *conn, sqlx::query(&sql)).await;
+    }
+
+    let column_types = get_column_types(&mut *conn, schema, table).await?;
+    let type_of = |name: &str| {
+        column_types
+            .get(name)
+            .ok_or_else(|| AppError::database(format!("Unknown column: {}", name)))
+    };
+
+    let mut col_list = Vec::with_capacity(columns.len());
+    let mut placeholders = Vec::with_capacity(columns.len());
+    for (i, col) in columns.iter().enumerate() {
+        if type_of(col)?.is_generated {
+            return Err(generated_column_error(col));
+        }
+        col_list.push(quote_ident(col));
+        placeholders.push(format!("${}::{}", i + 1, type_of(col)?.formatted));
+    }
+    let sql = format!(
+        "INSERT INTO {}.{} ({}) VALUES ({}) RETURNING *",
+        quote_ident(schema),
+        quote_ident(table),
+        col_list.join(", "),
+        placeholders.join(", ")
+    );
+
+    let mut q = sqlx::query(&sql);
+    for col in &columns {
+        q = json_to_param(values[*col].as_ref(), type_of(col)?)?.bind_to(q);
+    }
+
+    fetch_result(&mut *conn, q).await
+}
+
+/// Insert a copy of the row with the given primary key and return it as stored. Generated
+/// and auto-increment columns are left out so they get fresh values; the copy is made
+/// server-side with INSERT ... SELECT, so values keep their exact types. A primary key
+/// that isn't auto-generated would only conflict with the source row, so that is
+/// refused before anything runs, and so is a source row that doesn't exist.
+pub async fn duplicate_row(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    primary_key_columns: &[String],
+    primary_key_values: &[serde_json::Value],
+) -> Result<QueryResult, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    if primary_key_columns.is_empty() {
+        return Err(AppError::database(
+            "Table has no primary key; cannot duplicate".into(),
+        ));
+    }
+    if primary_key_columns.len() != primary_key_values.len() {
+        return Err(AppError::database(
+            "Primary key column/value count mismatch".into(),
+        ));
+    }
+    for pk_col in primary_key_columns {
+        if !is_valid_identifier(pk_col) {
+            return Err(AppError::database("Invalid primary key column name".into()));
+        }
+    }
+
+    let column_types = get_column_types(&mut *conn, schema, table).await?;
+    let type_of = |name: &str| {
+        column_types
+            .get(name)
+            .ok_or_else(|| AppError::database(format!("Unknown column: {}", name)))
+    };
+    for pk_col in primary_key_columns {
+        if !type_of(pk_col)?.is_auto_increment {
+            return Err(AppError::database(format!(
+                "Primary key column {} is not auto-generated, so a copy would conflict \
+                 with the original row; insert the row with a new key instead",
+                pk_col
+            )));
+        }
+    }
+
+    let mut copied: Vec<&String> = column_types
+        .iter()
+        .filter(|(_, t)| !t.is_generated && !t.is_auto_increment)
+        .map(|(name, _)| name)
+        .collect();
+    copied.sort();
+    let qualified = format!("{}.{}", quote_ident(schema), quote_ident(table));
+    let mut where_parts = Vec::with_capacity(primary_key_columns.len());
+    for (i, c) in primary_key_columns.iter().enumerate() {
+        where_parts.push(format!(
+            "{} = ${}::{}",
+            quote_ident(c),
+            i + 1,
+            type_of(c)?.formatted
+        ));
+    }
+    let where_clause = where_parts.join(" AND ");
+
+    let exists_sql = format!("SELECT 1 FROM {} WHERE {}", qualified, where_clause);
+    let mut exists = sqlx::query(&exists_sql);
+    for (c, v) in primary_key_columns.iter().zip(primary_key_values) {
+        exists = json_to_param(Some(v), type_of(c)?)?.bind_to(exists);
+    }
+    if exists
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(AppError::from_sqlx)?
+        .is_none()
+    {
+        return Err(AppError::database("Row not found".into()));
+    }
+
+    let sql = if copied.is_empty() {
+        // Nothing to copy: every column is generated, so the copy is all defaults.
+        format!("INSERT INTO {} DEFAULT VALUES RETURNING *", qualified)
+    } else {
+        let col_list = copied
+            .iter()
+            .map(|c| quote_ident(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "INSERT INTO {0} ({1}) SELECT {1} FROM {0} WHERE {2} RETURNING *",
+            qualified, col_list, where_clause
+        )
+    };
+
+    let mut q = sqlx::query(&sql);
+    if !copied.is_empty() {
+        for (c, v) in primary_key_columns.iter().zip(primary_key_values) {
+            q = json_to_param(Some(v), type_of(c)?)?.bind_to(q);
+        }
+    }
+    fetch_result(&mut *conn, q).await
+}
+
+/// Delete rows by primary key. Each inner vec is one row's PK values, in the order of
+/// `primary_key_columns`. Values are bound with the key columns' real types, and large
+/// deletes are split into batches that stay under Postgres's bind parameter limit. The
+/// batches run in one transaction, so a failing batch leaves every row in place.
+pub async fn delete_rows(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    primary_key_columns: &[String],
+    rows: &[Vec<serde_json::Value>],
+) -> Result<u64, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    if primary_key_columns.is_empty() {
+        return Err(AppError::database(
+            "Table has no primary key; cannot delete".into(),
+        ));
+    }
+    for pk_col in primary_key_columns {
+        if !is_valid_identifier(pk_col) {
+            return Err(AppError::database("Invalid primary key column name".into()));
+        }
+    }
+    if rows
+        .iter()
+        .any(|row| row.len() != primary_key_columns.len())
+    {
+        return Err(AppError::database(
+            "Primary key value count mismatch".into(),
+        ));
+    }
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let column_types = get_column_types(&mut *conn, schema, table).await?;
+    let pk_types = primary_key_columns
+        .iter()
+        .map(|c| {
+            column_types
+                .get(c)
+                .ok_or_else(|| AppError::database(format!("Unknown column: {}", c)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pk_cols_quoted: Vec<String> = primary_key_columns.iter().map(|c| quote_ident(c)).collect();
+    let pk_tuple = format!("({})", pk_cols_quoted.join(", "));
+
+    let rows_per_batch = MAX_BIND_PARAMS / primary_key_columns.len();
+    let mut tx = conn.begin().await.map_err(AppError::from_sqlx)?;
+    let mut rows_affected = 0;
+    for batch in rows.chunks(rows_per_batch) {
+        let mut param_idx = 1;
+        let mut value_tuples = Vec::with_capacity(batch.len());
+        for _ in batch {
+            let placeholders: Vec<String> = pk_types
+                .iter()
+                .map(|t| {
+                    let s = format!("${}::{}", param_idx, t.formatted);
+                    param_idx += 1;
+                    s
+                })
+                .collect();
+            value_tuples.push(format!("({})", placeholders.join(", ")));
+        }
+
+        let sql = format!(
+            "DELETE FROM {}.{} WHERE {} IN ({})",
+            quote_ident(schema),
+            quote_ident(table),
+            pk_tuple,
+            value_tuples.join(", ")
+        );
+
+        let mut q = sqlx::query(&sql);
+        for row in batch {
+            for (v, t) in row.iter().zip(&pk_types) {
+                q = json_to_param(Some(v), t)?.bind_to(q);
+            }
+        }
+
+        let result = q.execute(&mut *tx).await.map_err(AppError::from_sqlx)?;
+        rows_affected += result.rows_affected();
+    }
+
+    tx.commit().await.map_err(AppError::from_sqlx)?;
+    Ok(rows_affected)
+}
+
+/// Load a CSV file into an existing table with COPY ... FROM STDIN.
+/// With a header, CSV columns are matched to table columns by name (in any order; table
+/// columns missing from the file get their defaults). Without one they map by position.
+/// Returns the number of rows copied. A bad value aborts the whole import.
+pub async fn import_csv(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    path: &str,
+    has_header: bool,
+) -> Result<u64, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+
+    let column_list = if has_header {
+        let header: Vec<String> = csv::Reader::from_path(path)
+            .and_then(|mut reader| reader.headers().cloned())
+            .map_err(|e| AppError::Config(format!("Cannot read CSV header: {}", e)))?
+            .iter()
+            .map(str::to_string)
+            .collect();
+        let table_columns: Vec<String> = get_columns(pool, schema, table)
+            .await?
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        if table_columns.is_empty() {
+            return Err(AppError::database(format!(
+                "Table {}.{} not found",
+                schema, table
+            )));
+        }
+        if let Some(unknown) = header.iter().find(|name| !table_columns.contains(name)) {
+            return Err(AppError::database(format!(
+                "CSV column \"{}\" is not a column of {}.{}",
+                unknown, schema, table
+            )));
+        }
+        let quoted: Vec<String> = header.iter().map(|name| quote_ident(name)).collect();
+        format!(" ({})", quoted.join(", "))
+    } else {
+        String::new()
+    };
+
+    let sql = format!(
+        "COPY {}.{}{} FROM STDIN WITH (FORMAT csv, HEADER {})",
+        quote_ident(schema),
+        quote_ident(table),
+        column_list,
+        has_header
+    );
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| AppError::Config(format!("Cannot read CSV file: {}", e)))?;
+    let mut reader = tokio::io::BufReader::new(file);
+    let has_bom = reader
+        .fill_buf()
+        .await
+        .map_err(|e| AppError::Config(format!("Cannot read CSV file: {}", e)))?
+        .starts_with(b"\xEF\xBB\xBF");
+    if has_bom {
+        reader.consume(3);
+    }
+
+    // read_from streams the file to the server in buffer-sized chunks.
+    let mut copy = pool.copy_in_raw(&sql).await.map_err(copy_error)?;
+    let sent = copy.read_from(&mut reader).await.map(|_| ());
+    if let Err(e) = sent {
+        let _ = copy.abort(e.to_string()).await;
+        return Err(copy_error(e));
+    }
+    copy.finish().await.map_err(copy_error)
+}
+
+/// COPY reports where a bad value is in its error context
+/// ("COPY t, line 3, column b: ..."), which the plain message leaves out.
+fn copy_error(e: sqlx::Error) -> AppError {
+    let context = e
+        .as_database_error()
+        .and_then(|db| db.try_downcast_ref::<sqlx::postgres::PgDatabaseError>())
+        .and_then(|pg| pg.r#where());
+    match context {
+        Some(context) => AppError::database(format!("{} ({})", e, context)),
+        None => AppError::database(e.to_string()),
+    }
+}
+
+/// Build the body of a WHERE clause from structured filters, joined with AND.
+/// Placeholders are numbered from `first_param`; returns the clause and the parameters
+/// to bind, in placeholder order. `column_types` (from `get_column_types`) is used to
+/// reject unknown columns and to convert and cast each value to its column's type, as
+/// `update_cell` does.
+fn build_filter_clause(
+    filters: &[ColumnFilter],
+    column_types: &HashMap<String, ColumnType>,
+    first_param: usize,
+) -> Result<(String, Vec<SqlParam>), AppError> {
+    let mut parts = Vec::with_capacity(filters.len());
+    let mut params = Vec::new();
+    let mut param_idx = first_param;
+
+    for filter in filters {
+        if !is_valid_identifier(&filter.column) {
+            return Err(AppError::database("Invalid filter column name".into()));
+        }
+        let column_type = column_types
+            .get(&filter.column)
+            .ok_or_else(|| AppError::database(format!("Unknown column: {}", filter.column)))?;
+
+        let op = match filter.op {
+            FilterOp::IsNull => {
+                parts.push(format!("{} IS NULL", quote_ident(&filter.column)));
+                continue;
+            }
+            FilterOp::IsNotNull => {
+                parts.push(format!("{} IS NOT NULL", quote_ident(&filter.column)));
+                continue;
+            }
+            FilterOp::Like => {
+                parts.push(format!(
+                    "{}::text LIKE ${}",
+                    quote_ident(&filter.column),
+                    param_idx
+                ));
+                params.push(SqlParam::Text(serde_json_value_to_sql(&filter.value)));
+                param_idx += 1;
+                continue;
+            }
+            FilterOp::Eq => "=",
+            FilterOp::Neq => "<>",
+            FilterOp::Gt => ">",
+            FilterOp::Gte => ">=",
+            FilterOp::Lt => "<",
+            FilterOp::Lte => "<=",
+        };
+        parts.push(format!(
+            "{} {} ${}::{}",
+            quote_ident(&filter.column),
+            op,
+            param_idx,
+            column_type.formatted
+        ));
+        params.push(json_to_param(Some(&filter.value), column_type)?);
+        param_idx += 1;
+    }
+
+    Ok((parts.join(" AND "), params))
+}
+
+/// Set one column to a value on every row matching the filters.
+/// An empty filter list is refused unless `allow_all` is set, so a missing filter
+/// can't silently rewrite the whole table.
+pub async fn bulk_update(
+    conn: &mut PgConnection,
+    schema: &str,
+    table: &str,
+    column: &str,
+    new_value: &serde_json::Value,
+    filters: &[ColumnFilter],
+    allow_all: bool,
+) -> Result<u64, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) || !is_valid_identifier(column) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    if filters.is_empty() && !allow_all {
+        return Err(AppError::database(
+            "Refusing to update every row without a filter".into(),
+        ));
+    }
+
+    let column_types = get_column_types(&mut *conn, schema, table).await?;
+    let set_type = column_types
+        .get(column)
+        .ok_or_else(|| AppError::database(format!("Unknown column: {}", column)))?;
+    if set_type.is_generated {
+        return Err(generated_column_error(column));
+    }
+
+    let (where_clause, filter_params) = build_filter_clause(filters, &column_types, 2)?;
+    let mut sql = format!(
+        "UPDATE {}.{} SET {} = $1::{}",
+        quote_ident(schema),
+        quote_ident(table),
+        quote_ident(column),
+        set_type.formatted
+    );
+    if !where_clause.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&where_clause);
+    }
+
+    let mut q = json_to_param(Some(new_value), set_type)?.bind_to(sqlx::query(&sql));
+    for param in filter_params {
+        q = param.bind_to(q);
+    }
+
+    let result = q.execute(&mut *conn).await.map_err(AppError::from_sqlx)?;
+    Ok(result.rows_affected())
+}
+
+/// Fetch a page of a table's rows, filtered and sorted. `order_by` is a list of
+/// (column, ascending) pairs applied in order. Columns are checked against the table's
+/// own and filter values are bound as parameters, so nothing from the caller is
+/// spliced into the SQL unquoted.
+pub async fn browse_table(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    order_by: &[(String, bool)],
+    filters: &[ColumnFilter],
+    limit: i64,
+    offset: i64,
+) -> Result<QueryResult, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+    if limit < 0 || offset < 0 {
+        return Err(AppError::database(
+            "Limit and offset must not be negative".into(),
+        ));
+    }
+
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+    let column_types = get_column_types(&mut conn, schema, table).await?;
+    if column_types.is_empty() {
+        return Err(AppError::database(format!(
+            "Table {}.{} not found",
+            schema, table
+        )));
+    }
+
+    let (where_clause, filter_params) = build_filter_clause(filters, &column_types, 3)?;
+    let mut sql = format!(
+        "SELECT * FROM {}.{}",
+        quote_ident(schema),
+        quote_ident(table)
+    );
+    if !where_clause.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&where_clause);
+    }
+    if !order_by.is_empty() {
+        let mut keys = Vec::with_capacity(order_by.len());
+        for (column, ascending) in order_by {
+            if !is_valid_identifier(column) || !column_types.contains_key(column) {
+                return Err(AppError::database(format!("Unknown column: {}", column)));
+            }
+            let direction = if *ascending { "ASC" } else { "DESC" };
+            keys.push(format!("{} {}", quote_ident(column), direction));
+        }
+        sql.push_str(" ORDER BY ");
+        sql.push_str(&keys.join(", "));
+    }
+    sql.push_str(" LIMIT $1 OFFSET $2");
+
+    let mut q = sqlx::query(&sql).bind(limit).bind(offset);
+    for param in filter_params {
+        q = param.bind_to(q);
+    }
+    fetch_result(&mut conn, q).await
+}
+
+/// Convert serde_json::Value to a type sqlx can bind.
+/// We use a custom enum/struct to handle the variety of types.
+fn serde_json_value_to_sql(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            Some(serde_json::to_string(v).unwrap_or_default())
+        }
+    }
+}
+
+/// Generate one `INSERT INTO "schema"."table" (...) VALUES (...);` statement per row,
+/// with literals written according to each column's type. Generated columns are left out.
+/// `where_clause` is inserted into the SELECT verbatim, so it must come from the user
+/// running the export and never from data.
+pub async fn export_insert_sql(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    where_clause: Option<&str>,
+) -> Result<String, AppError> {
+    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
+        return Err(AppError::database("Invalid identifier".into()));
+    }
+
+    let columns: Vec<(String, String)> = sqlx::query(
+        r#"
+        SELECT a.attname::text AS name, COALESCE(bt.typname, t.typname)::text AS type_name
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_type t ON t.oid = a.atttypid
+        LEFT JOIN pg_type bt ON bt.oid = t.typbasetype AND t.typtype = 'd'
+        WHERE n.nspname = $1 AND c.relname = $2
+          AND a.attnum > 0 AND NOT a.attisdropped AND a.attgenerated = ''
+        ORDER BY a.attnum
+        "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from_sqlx)?
+    .iter()
+    .map(|row| (row.get("name"), row.get("type_name")))
+    .collect();
+    if columns.is_empty() {
+        return Err(AppError::database(format!(
+            "Table {}.{} not found",
+            schema, table
+        )));
+    }
+
+    let quoted: Vec<String> = columns.iter().map(|(name, _)| quote_ident(name)).collect();
+    let select_list: Vec<String> = quoted.iter().map(|c| format!("{}::text", c)).collect();
+    let mut sql = format!(
+        "SELECT {} FROM {}.{}",
+        select_list.join(", "),
+        quote_ident(schema),
+        quote_ident(table)
+    );
+    if let Some(where_clause) = where_clause.map(str::trim).filter(|w| !w.is_empty()) {
+        sql.push_str(" WHERE ");
+        sql.push_str(where_clause);
+    }
+
+    let insert_prefix = format!(
+        "INSERT INTO {}.{} ({}) VALUES",
+        quote_ident(schema),
+        quote_ident(table),
+        quoted.join(", ")
+    );
+    let mut out = String::new();
+    let mut stream = sqlx::query(&sql).fetch(pool);
+    while let Some(row) = stream.try_next().await.map_err(AppError::from_sqlx)? {
+        let mut literals = Vec::with_capacity(columns.len());
+        for (i, (_, type_name)) in columns.iter().enumerate() {
+            let value: Option<String> = row.try_get(i).map_err(AppError::from_sqlx)?;
+            literals.push(export::sql_literal(value.as_deref(), type_name));
+        }
+        out.push_str(&format!("{} ({});\n", insert_prefix, literals.join(", ")));
+    }
+
+    Ok(out)
+}
+
+/// Execute an arbitrary SQL query and return results as JSON values.
+pub async fn execute_query(pool: &PgPool, sql: &str) -> Result<QueryResult, AppError> {
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+    fetch_result(&mut conn, sqlx::query(sql)).await
+}
+
+/// Execute a query with `params` bound to `$1..$n` in order. Each parameter's type comes
+/// from its JSON value: integers bind as bigint, other numbers as double precision,
+/// strings as text, booleans as boolean, null as a NULL text, and arrays and objects as
+/// jsonb. Where the server can't convert a parameter implicitly, cast the placeholder in
+/// the SQL (e.g. `$1::date`).
+pub async fn execute_query_params(
+    pool: &PgPool,
+    sql: &str,
+    params: &[serde_json::Value],
+) -> Result<QueryResult, AppError> {
+    let mut q = sqlx::query(sql);
+    for param in params {
+        q = bind_json(q, param);
+    }
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+    fetch_result(&mut conn, q).await
+}
+
+fn bind_json<'q>(q: PgQuery<'q>, value: &serde_json::Value) -> PgQuery<'q> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => q.bind(None::<String>),
+        Value::Bool(b) => q.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => q.bind(i),
+            None => q.bind(n.as_f64()),
+        },
+        Value::String(s) => q.bind(s.clone()),
+        Value::Array(_) | Value::Object(_) => q.bind(value.clone()),
+    }
+}
+
+/// Execute a query with `statement_timeout` set for its duration.
+/// The timeout is set for the session rather than with SET LOCAL in a wrapping
+/// transaction, so statements that can't run inside one (VACUUM, CREATE INDEX
+/// CONCURRENTLY) and queries that manage their own transaction still work. It is reset
+/// afterwards whether or not the query succeeded; a connection that can't be reset is
+/// closed instead of going back to the pool.
+pub async fn execute_query_with_timeout(
+    pool: &PgPool,
+    sql: &str,
+    timeout_ms: u64,
+) -> Result<QueryResult, AppError> {
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+
+    // SET doesn't accept bind parameters; the value is a plain integer.
+    sqlx::query(&format!("SET statement_timeout = {}", timeout_ms))
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::from_sqlx)?;
+
+    let result = fetch_result(&mut conn, sqlx::query(sql)).await;
+
+    if sqlx::query("RESET statement_timeout")
+        .execute(&mut *conn)
+        .await
+        .is_err()
+    {
+        conn.close_on_drop();
+    }
+
+    result.map_err(|e| match e {
+        // 57014 query_canceled, raised here by the statement timeout.
+        AppError::Database { code, .. } if code.as_deref() == Some("57014") => AppError::database(
+            format!("Query cancelled: exceeded the {} ms timeout", timeout_ms),
+        ),
+        other => other,
+    })
+}
+
+/// Classify a single statement by its leading keyword, skipping comments and whitespace.
+/// For WITH queries the main statement decides, except that any data-modifying CTE makes
+/// the whole query DML. This is a client-side check only: a SELECT can still call a
+/// function that writes.
+pub fn classify_statement(sql: &str) -> StatementKind {
+    const DML: [&str; 4] = ["insert", "update", "delete", "merge"];
+
+    let words = script::words(sql);
+    let Some(first) = words.first() else {
+        return StatementKind::Utility;
+    };
+    // A parenthesized query like `(SELECT 1)` starts below depth 0.
+    let base = first.depth;
+    let top_level = |text: &str| words.iter().any(|w| w.depth == base && w.text == text);
+
+    let main = if first.text == "with" {
+        let main = words.iter().skip(1).find(|w| {
+            w.depth == base
+                && matches!(
+                    w.text.as_str(),
+                    "select" | "values" | "table" | "insert" | "update" | "delete" | "merge"
+                )
+        });
+        let modifying_cte = words
+            .iter()
+            .any(|w| w.opens_group && DML.contains(&w.text.as_str()));
+        match main {
+            Some(_) if modifying_cte => return StatementKind::Dml,
+            Some(main) => main.text.as_str(),
+            None => return StatementKind::Utility,
+        }
+    } else {
+        first.text.as_str()
+    };
+
+    match main {
+        "select" if top_level("into") => StatementKind::Ddl,
+        "select" | "values" | "table" => StatementKind::Select,
+        _ if DML.contains(&main) => StatementKind::Dml,
+        "create" | "alter" | "drop" | "truncate" | "comment" | "grant" | "revoke" => {
+            StatementKind::Ddl
+        }
+        _ => StatementKind::Utility,
+    }
+}
+
+/// Refuse `sql` unless every statement in it is a SELECT, for read-only query mode.
+pub fn ensure_select_only(sql: &str) -> Result<(), AppError> {
+    for statement in script::split_statements(sql) {
+        let kind = classify_statement(&statement);
+        if kind != StatementKind::Select {
+            let label = match kind {
+                StatementKind::Dml => "data-modifying",
+                StatementKind::Ddl => "DDL",
+                _ => "non-SELECT",
+            };
+            return Err(AppError::database(format!(
+                "Read-only mode: refusing {} statement",
+                label
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Refuse statements that would lift a read-only connection's protection for the rest
+/// of the session: `READ WRITE` transactions or characteristics, changing
+/// `default_transaction_read_only` / `transaction_read_only` (including through
+/// `set_config`), and `RESET ALL` / `DISCARD ALL`, which restore the server default.
+pub fn ensure_stays_read_only(sql: &str) -> Result<(), AppError> {
+    for statement in script::split_statements(sql) {
+        let words = script::words(&statement);
+        let text = |i: usize| words.get(i).map(|w| w.text.as_str());
+        let read_write = words
+            .windows(2)
+            .any(|pair| pair[0].text == "read" && pair[1].text == "write");
+        let reset_all = matches!((text(0), text(1)), (Some("reset" | "discard"), Some("all")));
+        // Checked on the raw text so the setting's name in a string literal counts too.
+        let touches_setting =
+            text(0) != Some("show") && statement.to_lowercase().contains("transaction_read_only");
+        if read_write || reset_all || touches_setting {
+            return Err(AppError::ReadOnly);
+        }
+    }
+    Ok(())
+}
+
+/// Get the JSON query plan for a statement.
+/// With `analyze` the statement really runs (with buffer usage collected), so it's done
+/// inside a transaction that is always rolled back; an INSERT or UPDATE leaves no trace.
+pub async fn explain_query(pool: &PgPool, sql: &str, analyze: bool) -> Result<String, AppError> {
+    Ok(explain_plan(pool, sql, analyze).await?.to_string())
+}
+
+/// The planner's estimated total cost and row count for a statement, from the root node of
+/// its (non-ANALYZE) plan.
+pub async fn estimate_query_cost(pool: &PgPool, sql: &str) -> Result<CostEstimate, AppError> {
+    let plan = explain_plan(pool, sql, false).await?;
+    let root = plan.get(0).and_then(|p| p.get("Plan"));
+    let field = |name: &str| root.and_then(|r| r.get(name)).and_then(|v| v.as_f64());
+    match (field("Total Cost"), field("Plan Rows")) {
+        (Some(total_cost), Some(plan_rows)) => Ok(CostEstimate {
+            total_cost,
+            plan_rows,
+        }),
+        _ => Err(AppError::database(
+            "EXPLAIN output has no cost estimate".into(),
+        )),
+    }
+}
+
+/// EXPLAIN (FORMAT JSON) output for a statement; see `explain_query`.
+async fn explain_plan(
+    pool: &PgPool,
+    sql: &str,
+    analyze: bool,
+) -> Result<serde_json::Value, AppError> {
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    let first_word = statement
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if first_word == "explain" {
+        return Err(AppError::database("Statement is already an EXPLAIN".into()));
+    }
+
+    let prefix = format!("EXPLAIN (FORMAT JSON, ANALYZE {0}, BUFFERS {0}) ", analyze);
+    let explain_sql = format!("{}{}", prefix, statement);
+
+    let mut tx = pool.begin().await.map_err(AppError::from_sqlx)?;
+    let row = sqlx::query(&explain_sql)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| shift_error_position(AppError::from_sqlx(e), sql, &prefix))?;
+    tx.rollback().await.map_err(AppError::from_sqlx)?;
+
+    row.try_get(0).map_err(AppError::from_sqlx)
+}
+
+/// Map the error position of a statement built as `prefix` + the trimmed `sql` (+ any
+/// suffix) back onto `sql` as the user wrote it. Positions inside the wrapper are dropped.
+fn shift_error_position(e: AppError, sql: &str, prefix: &str) -> AppError {
+    match e {
+        AppError::Database {
+            message,
+            code,
+            position: Some(position),
+        } => {
+            let leading = sql[..sql.len() - sql.trim_start().len()].chars().count();
+            let position = (position + leading)
+                .checked_sub(prefix.chars().count())
+                .filter(|&p| p > leading && p <= sql.chars().count());
+            AppError::Database {
+                message,
+                code,
+                position,
+            }
+        }
+        other => other,
+    }
+}
+
+/// Whether `sql` is a single plain SELECT. Anything with a `;` is treated as a
+/// possible multi-statement script and rejected, even if the `;` is inside a literal.
+fn is_single_select(sql: &str) -> bool {
+    let lowered = sql.trim_start().to_ascii_lowercase();
+    lowered
+        .strip_prefix("select")
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_whitespace() || c == '*'))
+        && !sql.contains(';')
+}
+
+/// Planner's row estimate for a query, from the root node of EXPLAIN (FORMAT JSON).
+async fn estimate_row_count(pool: &PgPool, sql: &str) -> Option<i64> {
+    let row = sqlx::query(&format!("EXPLAIN (FORMAT JSON) {}", sql))
+        .fetch_one(pool)
+        .await
+        .ok()?;
+    let plan: serde_json::Value = row.try_get(0).ok()?;
+    plan.get(0)?
+        .get("Plan")?
+        .get("Plan Rows")?
+        .as_f64()
+        .map(|rows| rows as i64)
+}
+
+/// Execute a query one page at a time. A single SELECT is wrapped as a subquery with
+/// LIMIT/OFFSET, so its own ORDER BY and LIMIT still apply, and `total_estimate` is set
+/// from the planner's estimate. Any other statement runs unchanged.
+pub async fn execute_query_paged(
     pool: &PgPool,
-    schema: &str,
-    table: &str,
-) -> Result<Vec<String>, AppError> {
-    let rows = sqlx::query(
-        r#"
-        SELECT kcu.column_name
-        FROM information_schema.table_constraints tc
-        JOIN information_schema.key_column_usage kcu
-          ON tc.constraint_name = kcu.constraint_name
-         AND tc.table_schema = kcu.table_schema
-         AND tc.table_name = kcu.table_name
-        WHERE tc.constraint_type = 'PRIMARY KEY'
-          AND tc.table_schema = $1 AND tc.table_name = $2
-        ORDER BY kcu.ordinal_position
-        "#,
-    )
-    .bind(schema)
-    .bind(table)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| AppError::Database(e.to_string()))?;
+    sql: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<QueryResult, AppError> {
+    let inner = sql.trim().trim_end_matches(';').trim_end();
+    if !is_single_select(inner) {
+        return execute_query(pool, sql).await;
+    }
 
-    Ok(rows.iter().map(|r| r.get("column_name")).collect())
+    // Newlines keep a trailing `-- comment` in the user's query from eating the wrapper.
+    let prefix = "SELECT * FROM (\n";
+    let paged_sql = format!("{}{}\n) AS bestgres_page LIMIT $1 OFFSET $2", prefix, inner);
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
+    let mut result = fetch_result(&mut conn, sqlx::query(&paged_sql).bind(limit).bind(offset))
+        .await
+        .map_err(|e| shift_error_position(e, sql, prefix))?;
+    drop(conn);
+    result.total_estimate = estimate_row_count(pool, inner).await;
+    Ok(result)
 }
 
-/// Validate that a string is a safe PostgreSQL identifier (for schema, table, column).
-fn is_valid_identifier(s: &str) -> bool {
-    !s.is_empty()
-        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
-        && s.chars().next().map(|c| !c.is_ascii_digit()).unwrap_or(false)
+/// Declare a NO SCROLL cursor `name` for a query. Must run inside a transaction, which
+/// holds the cursor (and its snapshot) until it ends.
+pub async fn declare_cursor(
+    conn: &mut PgConnection,
+    name: &str,
+    sql: &str,
+) -> Result<(), AppError> {
+    let inner = sql.trim().trim_end_matches(';').trim_end();
+    let prefix = format!("DECLARE {} NO SCROLL CURSOR FOR\n", quote_ident(name));
+    sqlx::query(&format!("{}{}", prefix, inner))
+        .execute(conn)
+        .await
+        .map_err(|e| shift_error_position(AppError::from_sqlx(e), sql, &prefix))?;
+    Ok(())
 }
 
-/// Update a single cell value. Uses parameterized queries for values; validates identifiers.
-pub async fn update_cell(
+/// Fetch the next `count` rows from an open cursor. An exhausted cursor returns no rows.
+pub async fn fetch_cursor(
+    conn: &mut PgConnection,
+    name: &str,
+    count: i64,
+) -> Result<QueryResult, AppError> {
+    // FETCH doesn't accept bind parameters; the count is a plain integer.
+    let sql = format!("FETCH FORWARD {} FROM {}", count, quote_ident(name));
+    let mut result = fetch_result(conn, sqlx::query(&sql)).await?;
+    // FETCH reports its row count as affected rows; it's not a modification.
+    result.rows_affected = None;
+    Ok(result)
+}
+
+/// Execute a query returning at most `max_rows` rows, as a safety cap. A single SELECT
+/// is wrapped with LIMIT `max_rows + 1` so the server stops early, and `truncated` is set
+/// if the extra row came back. Any other statement runs unchanged.
+pub async fn execute_query_capped(
     pool: &PgPool,
-    schema: &str,
-    table: &str,
-    column: &str,
-    primary_key_columns: &[String],
-    primary_key_values: &[serde_json::Value],
-    new_value: &serde_json::Value,
-) -> Result<u64, AppError> {
-    if !is_valid_identifier(schema) || !is_valid_identifier(table) || !is_valid_identifier(column) {
-        return Err(AppError::Database("Invalid identifier".into()));
+    sql: &str,
+    max_rows: usize,
+    timeout_ms: Option<u64>,
+) -> Result<QueryResult, AppError> {
+    let inner = sql.trim().trim_end_matches(';').trim_end();
+    let prefix = "SELECT * FROM (\n";
+    // One row past the cap tells whether there were more; LIMIT takes at most an i64.
+    let limit = max_rows.saturating_add(1).min(i64::MAX as usize);
+    let capped_sql = format!("{}{}\n) AS bestgres_capped LIMIT {}", prefix, inner, limit);
+    let wrapped = is_single_select(inner);
+    let run_sql = if wrapped { capped_sql.as_str() } else { sql };
+
+    let mut result = match timeout_ms {
+        Some(ms) => execute_query_with_timeout(pool, run_sql, ms).await,
+        None => execute_query(pool, run_sql).await,
     }
-    if primary_key_columns.is_empty() {
-        return Err(AppError::Database("Table has no primary key; cannot update".into()));
+    .map_err(|e| {
+        if wrapped {
+            shift_error_position(e, sql, prefix)
+        } else {
+            e
+        }
+    })?;
+    if result.rows.len() > max_rows {
+        result.rows.truncate(max_rows);
+        result.row_count = max_rows;
+        result.truncated = true;
     }
-    if primary_key_columns.len() != primary_key_values.len() {
-        return Err(AppError::Database("Primary key column/value count mismatch".into()));
+    Ok(result)
+}
+
+/// Run a query and collect its rows as JSON values, decoded as they stream in.
+/// Column names, types and decoders come from the first row; a query returning no rows
+/// still reports its columns from the statement it was prepared as.
+async fn fetch_result(
+    conn: &mut PgConnection,
+    query: PgQuery<'_>,
+) -> Result<QueryResult, AppError> {
+    let start = std::time::Instant::now();
+
+    // Preparing the statement before running it would cache it with the parameter types
+    // the server guesses, and the bound values (an i64 for an int4 column, say) would
+    // then be sent in the wrong binary format.
+    let sql = query.sql();
+    let mut columns: Vec<String> = Vec::new();
+    let mut column_types: Vec<String> = Vec::new();
+    let mut decoders: Vec<DecodeFn> = Vec::new();
+    let mut result_rows: Vec<Vec<serde_json::Value>> = Vec::new();
+    let mut rows_affected = 0;
+
+    {
+        let mut stream = (&mut *conn).fetch_many(query);
+        while let Some(step) = stream.try_next().await.map_err(AppError::from_sqlx)? {
+            match step {
+                sqlx::Either::Left(done) => rows_affected += done.rows_affected(),
+                sqlx::Either::Right(row) => {
+                    if decoders.is_empty() {
+                        column_types = row
+                            .columns()
+                            .iter()
+                            .map(|col| col.type_info().name().to_string())
+                            .collect();
+                        (columns, decoders) = decode::row_decoders(&row);
+                    }
+                    result_rows.push(decode::decode_row(&row, &decoders));
+                }
+            }
+        }
     }
-    for pk_col in primary_key_columns {
-        if !is_valid_identifier(pk_col) {
-            return Err(AppError::Database("Invalid primary key column name".into()));
+
+    // No rows to read the columns from: the statement ran with the bound types, so this
+    // is answered from the statement cache.
+    if result_rows.is_empty() {
+        let statement = (&mut *conn)
+            .prepare(sql)
+            .await
+            .map_err(AppError::from_sqlx)?;
+        columns = statement
+            .columns()
+            .iter()
+            .map(|col| col.name().to_string())
+            .collect();
+        column_types = statement
+            .columns()
+            .iter()
+            .map(|col| col.type_info().name().to_string())
+            .collect();
+    }
+
+    let execution_time_ms = start.elapsed().as_millis() as u64;
+    let row_count = result_rows.len();
+    let columns_empty = columns.is_empty();
+
+    Ok(QueryResult {
+        columns,
+        column_types,
+        rows: result_rows,
+        row_count,
+        execution_time_ms,
+        total_estimate: None,
+        rows_affected: columns_empty.then_some(rows_affected),
+        truncated: false,
+        editable: false,
+        primary_key_columns: Vec::new(),
+        source_schema: None,
+        source_table: None,
+    })
+}
+
+/// Run a query and pass each row to `on_row`, decoded to JSON, as it arrives instead of
+/// collecting the whole result set. `on_row` also receives the column names.
+/// Returns the number of rows seen.
+pub async fn for_each_row<F>(pool: &PgPool, sql: &str, mut on_row: F) -> Result<usize, AppError>
+where
+    F: FnMut(&[String], Vec<serde_json::Value>) -> Result<(), AppError>,
+{
+    let mut stream = sqlx::query(sql).fetch(pool);
+    let mut columns: Vec<String> = Vec::new();
+    let mut decoders: Vec<DecodeFn> = Vec::new();
+    let mut row_count = 0;
+
+    while let Some(row) = stream.try_next().await.map_err(AppError::from_sqlx)? {
+        if decoders.is_empty() {
+            (columns, decoders) = decode::row_decoders(&row);
         }
+        on_row(&columns, decode::decode_row(&row, &decoders))?;
+        row_count += 1;
     }
 
-    // Build: UPDATE "schema"."table" SET "column" = $1 WHERE "pk1" = $2 AND "pk2" = $3 ...
-    let set_clause = format!(r#""{}" = $1"#, column);
-    let mut param_idx = 2u32;
-    let where_parts: Vec<String> = primary_key_columns
-        .iter()
-        .map(|c| {
-            let part = format!(r#""{}" = ${}"#, c, param_idx);
-            param_idx += 1;
-            part
-        })
-        .collect();
-    let where_clause = where_parts.join(" AND ");
-    let sql = format!(
-        r#"UPDATE "{}"."{}" SET {} WHERE {}"#,
-        schema,
-        table,
-        set_clause,
-        where_clause
-    );
+    Ok(row_count)
+}
 
-    let mut q = sqlx::query(&sql).bind(serde_json_value_to_sql(new_value));
+/// Run a script of several statements in order on one connection, collecting a result
+/// per statement. Stops at the first failing statement and returns the results so far
+/// alongside its error.
+pub async fn execute_script(pool: &PgPool, sql: &str) -> Result<ScriptResult, AppError> {
+    let mut conn = pool.acquire().await.map_err(AppError::from_sqlx)?;
 
-    for v in primary_key_values {
-        q = q.bind(serde_json_value_to_sql(v));
+    let mut results = Vec::new();
+    for statement in script::split_statements(sql) {
+        match fetch_result(&mut conn, sqlx::query(&statement)).await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                return Ok(ScriptResult {
+                    results,
+                    error: Some(e.to_string()),
+                })
+            }
+        }
     }
 
-    let result = q.execute(pool).await.map_err(|e| AppError::Database(e.to_string()))?;
-    Ok(result.rows_affected())
+    Ok(ScriptResult {
+        results,
+        error: None,
+    })
 }
 
-/// Map information_schema data_type to PostgreSQL cast for text-bound params.
-fn sql_cast_for_type(data_type: &str) -> Option<&'static str> {
-    let t = data_type.to_lowercase();
-    if t.contains("timestamp with time zone") || t == "timestamptz" {
-        return Some("timestamptz");
+/// Run a script inside a transaction that is always rolled back, reporting how each
+/// statement went. Like `execute_script` it stops at the first failure (later statements
+/// would only see an aborted transaction). Scripts with their own transaction control
+/// (BEGIN, COMMIT, ...) are refused, since a COMMIT would make the changes stick.
+pub async fn dry_run_script(pool: &PgPool, sql: &str) -> Result<Vec<StatementOutcome>, AppError> {
+    const TRANSACTION_CONTROL: [&str; 8] = [
+        "begin",
+        "start",
+        "commit",
+        "end",
+        "rollback",
+        "abort",
+        "savepoint",
+        "release",
+    ];
+
+    let statements = script::split_statements(sql);
+    for statement in &statements {
+        let words = script::words(statement);
+        let keyword = |i: usize| words.get(i).map_or("", |w| w.text.as_str());
+        if TRANSACTION_CONTROL.contains(&keyword(0))
+            || (keyword(0) == "prepare" && keyword(1) == "transaction")
+        {
+            return Err(AppError::database(
+                "Dry run can't include transaction control statements".into(),
+            ));
+        }
     }
-    if t.contains("timestamp without time zone")
-        || t == "timestamp"
-        || (t.contains("timestamp") && !t.contains("with"))
-    {
-        return Some("timestamp");
+
+    let mut tx = pool.begin().await.map_err(AppError::from_sqlx)?;
+    let mut outcomes = Vec::with_capacity(statements.len());
+    for statement in statements {
+        let outcome = match fetch_result(&mut tx, sqlx::query(&statement)).await {
+            Ok(result) => StatementOutcome {
+                statement,
+                success: true,
+                rows_affected: result.rows_affected,
+                error: None,
+            },
+            Err(e) => StatementOutcome {
+                statement,
+                success: false,
+                rows_affected: None,
+                error: Some(e.to_string()),
+            },
+        };
+        let failed = !outcome.success;
+        outcomes.push(outcome);
+        if failed {
+            break;
+        }
     }
-    if t == "date" {
-        return Some("date");
+    tx.rollback().await.map_err(AppError::from_sqlx)?;
+    Ok(outcomes)
+}
+
+/// Describe a query without running it and map each result column back to the
+/// base table column it was read from. Uses the table OID / attribute number that
+/// Postgres reports in the RowDescription; computed columns have no source.
+pub async fn describe_query_sources(
+    pool: &PgPool,
+    sql: &str,
+) -> Result<Vec<ColumnSource>, AppError> {
+    let describe = pool.describe(sql).await.map_err(AppError::from_sqlx)?;
+
+    // Look up every source table's attributes in one round trip.
+    let mut relation_ids: Vec<i64> = describe
+        .columns()
+        .iter()
+        .filter_map(|col| col.relation_id())
+        .map(|oid| i64::from(oid.0))
+        .collect();
+    relation_ids.sort_unstable();
+    relation_ids.dedup();
+
+    let mut attributes: HashMap<(i64, i16), (String, String, String)> = HashMap::new();
+    if !relation_ids.is_empty() {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.attrelid::int8 AS relation_id, a.attnum,
+                   n.nspname AS schema, c.relname AS table_name, a.attname AS column_name
+            FROM pg_attribute a
+            JOIN pg_class c ON c.oid = a.attrelid
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE a.attrelid = ANY($1::oid[]) AND a.attnum > 0
+            "#,
+        )
+        .bind(&relation_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from_sqlx)?;
+        for row in rows {
+            attributes.insert(
+                (row.get("relation_id"), row.get("attnum")),
+                (
+                    row.get("schema"),
+                    row.get("table_name"),
+                    row.get("column_name"),
+                ),
+            );
+        }
     }
-    if t.contains("time with time zone") || t == "timetz" {
-        return Some("timetz");
+
+    let sources = describe
+        .columns()
+        .iter()
+        .map(|col| {
+            let source = col
+                .relation_id()
+                .zip(col.relation_attribute_no())
+                .and_then(|(oid, attnum)| attributes.get(&(i64::from(oid.0), attnum)))
+                .cloned();
+            let (source_schema, source_table, source_column) = match source {
+                Some((schema, table, column)) => (Some(schema), Some(table), Some(column)),
+                None => (None, None, None),
+            };
+            ColumnSource {
+                name: col.name().to_string(),
+                data_type: col.type_info().name().to_string(),
+                source_schema,
+                source_table,
+                source_column,
+            }
+        })
+        .collect();
+
+    Ok(sources)
+}
+
+/// Keywords that make a SELECT's rows not map one-to-one onto rows of its table.
+const NOT_EDITABLE_KEYWORDS: &[&str] = &[
+    "join",
+    "group",
+    "distinct",
+    "union",
+    "intersect",
+    "except",
+    "having",
+    "window",
+];
+
+/// Mark a query result as editable when the query is a simple single-table SELECT: every
+/// result column is a column of the same table under its own name, and the table's
+/// primary key is among them. Joins, grouping, DISTINCT, set operations and computed
+/// columns leave the result as it is.
+pub async fn mark_editable(
+    pool: &PgPool,
+    sql: &str,
+    result: &mut QueryResult,
+) -> Result<(), AppError> {
+    let inner = sql.trim().trim_end_matches(';').trim_end();
+    if result.columns.is_empty() || !is_single_select(inner) {
+        return Ok(());
     }
-    if t.contains("time without time zone") || t == "time" {
-        return Some("time");
+    if script::words(inner)
+        .iter()
+        .any(|w| w.depth == 0 && NOT_EDITABLE_KEYWORDS.contains(&w.text.as_str()))
+    {
+        return Ok(());
     }
-    if t == "json" {
-        return Some("json");
+
+    let sources = describe_query_sources(pool, inner).await?;
+    let mut table: Option<(&String, &String)> = None;
+    for source in &sources {
+        let (Some(schema), Some(name), Some(column)) = (
+            &source.source_schema,
+            &source.source_table,
+            &source.source_column,
+        ) else {
+            return Ok(());
+        };
+        if *column != source.name || table.is_some_and(|t| t != (schema, name)) {
+            return Ok(());
+        }
+        table = Some((schema, name));
     }
-    if t == "jsonb" {
-        return Some("jsonb");
+    let Some((schema, name)) = table else {
+        return Ok(());
+    };
+
+    let primary_key = get_primary_key_columns(pool, schema, name).await?;
+    if primary_key.is_empty() || !primary_key.iter().all(|c| result.columns.contains(c)) {
+        return Ok(());
     }
-    if t == "uuid" {
-        return Some("uuid");
+    result.editable = true;
+    result.primary_key_columns = primary_key;
+    result.source_schema = Some(schema.clone());
+    result.source_table = Some(name.clone());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column_type(formatted: &str, type_name: &str) -> ColumnType {
+        ColumnType {
+            formatted: formatted.into(),
+            type_name: type_name.into(),
+            element_type: None,
+            is_generated: false,
+            is_auto_increment: false,
+        }
     }
-    if t == "boolean" || t == "bool" {
-        return Some("boolean");
+
+    fn filter(column: &str, op: FilterOp, value: serde_json::Value) -> ColumnFilter {
+        ColumnFilter {
+            column: column.into(),
+            op,
+            value,
+        }
     }
-    if t == "integer" || t == "int" || t == "int4" {
-        return Some("integer");
+
+    fn filter_columns() -> HashMap<String, ColumnType> {
+        HashMap::from([
+            ("id".to_string(), column_type("integer", "int4")),
+            ("mood".to_string(), column_type("public.mood", "mood")),
+            ("addr".to_string(), column_type("inet", "inet")),
+            ("name".to_string(), column_type("text", "text")),
+        ])
     }
-    if t == "bigint" || t == "int8" {
-        return Some("bigint");
+
+    #[test]
+    fn filter_placeholders_are_cast_to_column_types() {
+        let filters = [
+            filter("mood", FilterOp::Eq, json!("happy")),
+            filter("addr", FilterOp::Neq, json!("10.0.0.1")),
+            filter("name", FilterOp::Like, json!("a%")),
+            filter("id", FilterOp::IsNull, json!(null)),
+        ];
+        let (clause, params) = build_filter_clause(&filters, &filter_columns(), 3).unwrap();
+        assert_eq!(
+            clause,
+            "\"mood\" = $3::public.mood AND \"addr\" <> $4::inet \
+             AND \"name\"::text LIKE $5 AND \"id\" IS NULL"
+        );
+        assert_eq!(params.len(), 3);
     }
-    if t == "smallint" || t == "int2" {
-        return Some("smallint");
+
+    #[test]
+    fn filter_rejects_unknown_column_and_bad_value() {
+        let unknown = [filter("missing", FilterOp::Eq, json!(1))];
+        assert!(build_filter_clause(&unknown, &filter_columns(), 1).is_err());
+
+        let not_a_number = [filter("id", FilterOp::Gt, json!("abc"))];
+        assert!(build_filter_clause(&not_a_number, &filter_columns(), 1).is_err());
     }
-    if t == "real" || t == "float4" {
-        return Some("real");
+
+    #[test]
+    fn classify_by_leading_keyword() {
+        let cases = [
+            ("SELECT 1", StatementKind::Select),
+            ("-- comment\n  values (1)", StatementKind::Select),
+            ("(SELECT 1) UNION (SELECT 2)", StatementKind::Select),
+            ("SELECT * INTO backup FROM t", StatementKind::Ddl),
+            ("UPDATE t SET a = 1", StatementKind::Dml),
+            ("MERGE INTO t USING s ON true", StatementKind::Dml),
+            ("CREATE TABLE t (a int)", StatementKind::Ddl),
+            ("grant select on t to r", StatementKind::Ddl),
+            ("SET search_path = app", StatementKind::Utility),
+            ("EXPLAIN DELETE FROM t", StatementKind::Utility),
+            ("", StatementKind::Utility),
+        ];
+        for (sql, kind) in cases {
+            assert_eq!(classify_statement(sql), kind, "{}", sql);
+        }
     }
-    if t == "double precision" || t == "float8" {
-        return Some("double precision");
+
+    #[test]
+    fn classify_with_queries() {
+        assert_eq!(
+            classify_statement("WITH x AS (SELECT 1) SELECT * FROM x"),
+            StatementKind::Select
+        );
+        assert_eq!(
+            classify_statement("WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone"),
+            StatementKind::Dml
+        );
+        assert_eq!(
+            classify_statement("WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x"),
+            StatementKind::Dml
+        );
+        // A keyword inside a string doesn't count.
+        assert_eq!(
+            classify_statement("WITH x AS (SELECT '(delete') SELECT * FROM x"),
+            StatementKind::Select
+        );
     }
-    if t == "numeric" || t == "decimal" {
-        return Some("numeric");
+
+    #[test]
+    fn select_only_refuses_any_other_statement() {
+        assert!(ensure_select_only("SELECT 1; SELECT 2").is_ok());
+        assert!(ensure_select_only("SELECT 1; DELETE FROM t").is_err());
+        assert!(ensure_select_only("SELECT 'a;b'; SHOW work_mem").is_err());
     }
-    None
-}
 
-/// Insert a new row. Columns and values must match in length.
-/// Values are bound as parameters. Use JsonValue::Null for NULL.
-/// column_types: info_schema data_type per column, used for proper casts (timestamptz, etc.).
-pub async fn insert_row(
-    pool: &PgPool,
-    schema: &str,
-    table: &str,
-    columns: &[String],
-    values: &[serde_json::Value],
-    column_types: &[String],
-) -> Result<u64, AppError> {
-    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
-        return Err(AppError::Database("Invalid identifier".into()));
+    async fn test_pool() -> PgPool {
+        let url = std::env::var("BESTGRES_TEST_DATABASE_URL").unwrap();
+        PgPool::connect(&url).await.unwrap()
     }
-    if columns.len() != values.len() {
-        return Err(AppError::Database("Column/value count mismatch".into()));
+
+    /// Create `public.<name>` from `columns`, dropping any leftover from an earlier run.
+    async fn test_table(pool: &PgPool, name: &str, columns: &str) {
+        sqlx::raw_sql(&format!(
+            "DROP TABLE IF EXISTS public.{name}; CREATE TABLE public.{name} ({columns})"
+        ))
+        .execute(pool)
+        .await
+        .unwrap();
     }
-    if columns.is_empty() {
-        return Err(AppError::Database("No columns specified".into()));
+
+    async fn drop_table(pool: &PgPool, name: &str) {
+        sqlx::raw_sql(&format!("DROP TABLE public.{name}"))
+            .execute(pool)
+            .await
+            .unwrap();
     }
-    for col in columns {
-        if !is_valid_identifier(col) {
-            return Err(AppError::Database("Invalid column name".into()));
+
+    #[test]
+    fn read_only_bypasses_are_refused() {
+        for sql in [
+            "SET default_transaction_read_only = off",
+            "set session default_transaction_read_only to false",
+            "SELECT 1; BEGIN READ WRITE",
+            "START TRANSACTION ISOLATION LEVEL SERIALIZABLE, READ WRITE",
+            "SET SESSION CHARACTERISTICS AS TRANSACTION READ WRITE",
+            "SET transaction_read_only = off",
+            "SELECT set_config('default_transaction_read_only', 'off', false)",
+            "RESET default_transaction_read_only",
+            "RESET ALL",
+            "DISCARD ALL",
+        ] {
+            assert!(
+                matches!(ensure_stays_read_only(sql), Err(AppError::ReadOnly)),
+                "{sql}"
+            );
+        }
+        for sql in [
+            "SELECT 'read write'",
+            "BEGIN READ ONLY",
+            "SHOW default_transaction_read_only",
+            "INSERT INTO t VALUES (1)",
+        ] {
+            assert!(ensure_stays_read_only(sql).is_ok(), "{sql}");
         }
     }
 
-    let col_list: Vec<String> = columns.iter().map(|c| format!(r#""{}""#, c)).collect();
-    let placeholders: Vec<String> = columns
-        .iter()
-        .enumerate()
-        .map(|(i, _)| {
-            let param = format!("${}", i + 1);
-            let cast = column_types
-                .get(i)
-                .and_then(|t| sql_cast_for_type(t));
-            match cast {
-                Some(c) => format!("{}::{}", param, c),
-                None => param,
-            }
-        })
-        .collect();
-    let sql = format!(
-        r#"INSERT INTO "{}"."{}" ({}) VALUES ({})"#,
-        schema,
-        table,
-        col_list.join(", "),
-        placeholders.join(", ")
-    );
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn params_bind_against_int4_columns() {
+        let pool = test_pool().await;
+        test_table(&pool, "bestgres_params", "id int4").await;
+        sqlx::query("INSERT INTO public.bestgres_params VALUES (1), (2)")
+            .execute(&pool)
+            .await
+            .unwrap();
 
-    let mut q = sqlx::query(&sql);
-    for v in values {
-        q = q.bind(serde_json_value_to_sql(v));
+        let sql = "SELECT id FROM public.bestgres_params WHERE id = $1";
+        // Twice: the second run goes through the statement cache.
+        for _ in 0..2 {
+            let result = execute_query_params(&pool, sql, &[json!(2)]).await.unwrap();
+            assert_eq!(result.columns, vec!["id"]);
+            assert_eq!(result.column_types, vec!["INT4"]);
+            assert_eq!(result.rows, vec![vec![json!(2)]]);
+        }
+        let empty = execute_query_params(&pool, sql, &[json!(3)]).await.unwrap();
+        assert_eq!(empty.columns, vec!["id"]);
+        assert_eq!(empty.rows_affected, None);
+        drop_table(&pool, "bestgres_params").await;
     }
 
-    let result = q.execute(pool).await.map_err(|e| AppError::Database(e.to_string()))?;
-    Ok(result.rows_affected())
-}
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn insert_and_duplicate_with_int4_columns() {
+        let pool = test_pool().await;
+        test_table(&pool, "bestgres_insert", "id serial PRIMARY KEY, n int4").await;
+        let mut conn = pool.acquire().await.unwrap();
 
-/// Delete rows by primary key. Each inner vec is one row's PK values.
-pub async fn delete_rows(
-    pool: &PgPool,
-    schema: &str,
-    table: &str,
-    primary_key_columns: &[String],
-    primary_key_values_list: &[Vec<serde_json::Value>],
-) -> Result<u64, AppError> {
-    if !is_valid_identifier(schema) || !is_valid_identifier(table) {
-        return Err(AppError::Database("Invalid identifier".into()));
-    }
-    if primary_key_columns.is_empty() {
-        return Err(AppError::Database("Table has no primary key; cannot delete".into()));
-    }
-    for pk_col in primary_key_columns {
-        if !is_valid_identifier(pk_col) {
-            return Err(AppError::Database("Invalid primary key column name".into()));
+        let values = HashMap::from([("n".to_string(), Some(json!(5)))]);
+        for expected_id in 1..=2 {
+            let inserted = insert_row(&mut conn, "public", "bestgres_insert", &values)
+                .await
+                .unwrap();
+            assert_eq!(inserted.columns, vec!["id", "n"]);
+            assert_eq!(inserted.rows, vec![vec![json!(expected_id), json!(5)]]);
         }
-    }
-    if primary_key_values_list.is_empty() {
-        return Ok(0);
+
+        let copy = duplicate_row(
+            &mut conn,
+            "public",
+            "bestgres_insert",
+            &["id".to_string()],
+            &[json!(1)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(copy.rows, vec![vec![json!(3), json!(5)]]);
+        drop(conn);
+        drop_table(&pool, "bestgres_insert").await;
     }
 
-    let pk_cols_quoted: Vec<String> = primary_key_columns
-        .iter()
-        .map(|c| format!(r#""{}""#, c))
-        .collect();
-    let pk_tuple = format!("({})", pk_cols_quoted.join(", "));
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn browse_filters_on_int4_columns() {
+        let pool = test_pool().await;
+        test_table(&pool, "bestgres_browse", "id int4, name text").await;
+        sqlx::query("INSERT INTO public.bestgres_browse VALUES (1, 'a'), (2, 'b')")
+            .execute(&pool)
+            .await
+            .unwrap();
 
-    let mut param_idx = 1u32;
-    let mut value_tuples = Vec::with_capacity(primary_key_values_list.len());
-    for row_vals in primary_key_values_list {
-        if row_vals.len() != primary_key_columns.len() {
-            return Err(AppError::Database("Primary key value count mismatch".into()));
+        let filters = [filter("id", FilterOp::Eq, json!(2))];
+        for _ in 0..2 {
+            let result = browse_table(&pool, "public", "bestgres_browse", &[], &filters, 100, 0)
+                .await
+                .unwrap();
+            assert_eq!(result.rows, vec![vec![json!(2), json!("b")]]);
         }
-        let placeholders: Vec<String> = (0..row_vals.len())
-            .map(|_| {
-                let s = format!("${}", param_idx);
-                param_idx += 1;
-                s
-            })
-            .collect();
-        value_tuples.push(format!("({})", placeholders.join(", ")));
+        drop_table(&pool, "bestgres_browse").await;
     }
 
-    let in_clause = value_tuples.join(", ");
-    let sql = format!(
-        r#"DELETE FROM "{}"."{}" WHERE {} IN ({})"#,
-        schema, table, pk_tuple, in_clause
-    );
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn result_columns_map_to_their_source_tables() {
+        let pool = test_pool().await;
+        test_table(&pool, "bestgres_src_a", "id int4 PRIMARY KEY, name text").await;
+        test_table(&pool, "bestgres_src_b", "a_id int4, note text").await;
 
-    let mut q = sqlx::query(&sql);
-    for row_vals in primary_key_values_list {
-        for v in row_vals {
-            q = q.bind(serde_json_value_to_sql(v));
-        }
+        let sql = "SELECT a.name, b.note, 1 AS one FROM public.bestgres_src_a a \
+                   JOIN public.bestgres_src_b b ON b.a_id = a.id";
+        let sources = describe_query_sources(&pool, sql).await.unwrap();
+        let mapped: Vec<_> = sources
+            .iter()
+            .map(|s| (s.source_table.as_deref(), s.source_column.as_deref()))
+            .collect();
+        assert_eq!(
+            mapped,
+            vec![
+                (Some("bestgres_src_a"), Some("name")),
+                (Some("bestgres_src_b"), Some("note")),
+                (None, None),
+            ]
+        );
+
+        let sql = "SELECT id, name FROM public.bestgres_src_a";
+        let mut result = execute_query(&pool, sql).await.unwrap();
+        mark_editable(&pool, sql, &mut result).await.unwrap();
+        assert!(result.editable);
+        assert_eq!(result.primary_key_columns, vec!["id"]);
+        assert_eq!(result.source_table.as_deref(), Some("bestgres_src_a"));
+
+        drop_table(&pool, "bestgres_src_a").await;
+        drop_table(&pool, "bestgres_src_b").await;
     }
 
-    let result = q.execute(pool).await.map_err(|e| AppError::Database(e.to_string()))?;
-    Ok(result.rows_affected())
-}
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn statement_timeout_cancels_and_is_reset() {
+        // One connection, so the follow-up queries see the same session.
+        let url = std::env::var("BESTGRES_TEST_DATABASE_URL").unwrap();
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await
+            .unwrap();
 
-/// Convert serde_json::Value to a type sqlx can bind.
-/// We use a custom enum/struct to handle the variety of types.
-fn serde_json_value_to_sql(v: &serde_json::Value) -> Option<String> {
-    match v {
-        serde_json::Value::Null => None,
-        serde_json::Value::Bool(b) => Some(b.to_string()),
-        serde_json::Value::Number(n) => Some(n.to_string()),
-        serde_json::Value::String(s) => Some(s.clone()),
-        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-            Some(serde_json::to_string(v).unwrap_or_default())
+        let err = execute_query_with_timeout(&pool, "SELECT pg_sleep(2)", 200)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("exceeded the 200 ms timeout"),
+            "{err}"
+        );
+
+        let timeout: String = sqlx::query_scalar("SHOW statement_timeout")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(timeout, "0");
+
+        // Statements that refuse to run inside a transaction block.
+        test_table(&pool, "bestgres_timeout", "id int4").await;
+        for sql in [
+            "VACUUM public.bestgres_timeout",
+            "CREATE INDEX CONCURRENTLY ON public.bestgres_timeout (id)",
+        ] {
+            execute_query_with_timeout(&pool, sql, 5_000).await.unwrap();
         }
+        drop_table(&pool, "bestgres_timeout").await;
     }
-}
 
-/// Execute an arbitrary SQL query and return results as JSON values.
-pub async fn execute_query(pool: &PgPool, sql: &str) -> Result<QueryResult, AppError> {
-    let start = std::time::Instant::now();
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn batched_delete_is_all_or_nothing() {
+        let pool = test_pool().await;
+        test_table(&pool, "bestgres_delete", "id int4 PRIMARY KEY").await;
+        test_table(
+            &pool,
+            "bestgres_delete_ref",
+            "id int4 REFERENCES public.bestgres_delete",
+        )
+        .await;
+        let count = (MAX_BIND_PARAMS + 10) as i32;
+        sqlx::query("INSERT INTO public.bestgres_delete SELECT generate_series(1, $1)")
+            .bind(count)
+            .execute(&pool)
+            .await
+            .unwrap();
+        // The last row is still referenced, so the second batch fails.
+        sqlx::query("INSERT INTO public.bestgres_delete_ref VALUES ($1)")
+            .bind(count)
+            .execute(&pool)
+            .await
+            .unwrap();
 
-    let rows = sqlx::query(sql)
-        .fetch_all(pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        let keys = ["id".to_string()];
+        let rows: Vec<_> = (1..=count).map(|id| vec![json!(id)]).collect();
+        let mut conn = pool.acquire().await.unwrap();
+        let err = delete_rows(&mut conn, "public", "bestgres_delete", &keys, &rows)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Database { code: Some(ref c), .. } if c == "23503"));
+        let left: i64 = sqlx::query_scalar("SELECT count(*) FROM public.bestgres_delete")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(left, i64::from(count));
 
-    let execution_time_ms = start.elapsed().as_millis() as u64;
+        let unreferenced = &rows[..rows.len() - 1];
+        let deleted = delete_rows(&mut conn, "public", "bestgres_delete", &keys, unreferenced)
+            .await
+            .unwrap();
+        assert_eq!(deleted, unreferenced.len() as u64);
+        drop(conn);
+        drop_table(&pool, "bestgres_delete_ref").await;
+        drop_table(&pool, "bestgres_delete").await;
+    }
 
-    let columns: Vec<String> = if let Some(first_row) = rows.first() {
-        first_row
-            .columns()
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn capped_results_are_marked_truncated() {
+        let pool = test_pool().await;
+        let sql = "SELECT generate_series(1, 10) AS n;";
+        let cases = [(3, 3, true), (10, 10, false), (usize::MAX, 10, false)];
+        for (max_rows, row_count, truncated) in cases {
+            let result = execute_query_capped(&pool, sql, max_rows, None)
+                .await
+                .unwrap();
+            assert_eq!(result.rows.len(), row_count, "{max_rows}");
+            assert_eq!(result.row_count, row_count, "{max_rows}");
+            assert_eq!(result.truncated, truncated, "{max_rows}");
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn database_sizes_cover_every_database() {
+        let pool = test_pool().await;
+        let sizes = list_database_sizes(&pool).await.unwrap();
+        let mut names: Vec<String> = sizes.iter().map(|s| s.name.clone()).collect();
+        names.sort();
+        assert_eq!(names, list_databases(&pool).await.unwrap());
+        assert!(sizes.windows(2).all(|pair| pair[0].bytes >= pair[1].bytes));
+        assert!(sizes.iter().all(|s| s.bytes > 0));
+    }
+
+    /// The first column of every row of a result, as integers.
+    fn first_column(result: &QueryResult) -> Vec<i64> {
+        result
+            .rows
             .iter()
-            .map(|c| c.name().to_string())
+            .map(|row| row[0].as_i64().unwrap())
             .collect()
-    } else {
-        Vec::new()
-    };
+    }
 
-    let result_rows: Vec<Vec<serde_json::Value>> = rows
-        .iter()
-        .map(|row| {
-            columns
-                .iter()
-                .enumerate()
-                .map(|(i, _)| {
-                    // Try types from most common to least common.
-                    // String covers text, varchar, char, etc.
-                    if let Ok(v) = row.try_get::<String, _>(i) {
-                        serde_json::Value::String(v)
-                    } else if let Ok(v) = row.try_get::<bool, _>(i) {
-                        serde_json::json!(v)
-                    } else if let Ok(v) = row.try_get::<i16, _>(i) {
-                        serde_json::json!(v)
-                    } else if let Ok(v) = row.try_get::<i32, _>(i) {
-                        serde_json::json!(v)
-                    } else if let Ok(v) = row.try_get::<i64, _>(i) {
-                        serde_json::json!(v)
-                    } else if let Ok(v) = row.try_get::<f32, _>(i) {
-                        serde_json::json!(v)
-                    } else if let Ok(v) = row.try_get::<f64, _>(i) {
-                        serde_json::json!(v)
-                    } else if let Ok(v) = row.try_get::<uuid::Uuid, _>(i) {
-                        serde_json::Value::String(v.to_string())
-                    } else if let Ok(v) = row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
-                        serde_json::Value::String(v.to_rfc3339())
-                    } else if let Ok(v) = row.try_get::<chrono::NaiveDateTime, _>(i) {
-                        serde_json::Value::String(v.to_string())
-                    } else if let Ok(v) = row.try_get::<chrono::NaiveDate, _>(i) {
-                        serde_json::Value::String(v.to_string())
-                    } else if let Ok(v) = row.try_get::<chrono::NaiveTime, _>(i) {
-                        serde_json::Value::String(v.to_string())
-                    } else if let Ok(v) = row.try_get::<serde_json::Value, _>(i) {
-                        v
-                    } else {
-                        serde_json::Value::Null
-                    }
-                })
-                .collect()
-        })
-        .collect();
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn paging_keeps_the_query_limit() {
+        let pool = test_pool().await;
+        let sql = "SELECT n FROM generate_series(1, 10) AS n ORDER BY n";
+        let page = execute_query_paged(&pool, sql, 3, 2).await.unwrap();
+        assert_eq!(first_column(&page), vec![3, 4, 5]);
+        assert!(page.total_estimate.is_some());
 
-    let row_count = result_rows.len();
+        let limited = format!("{} LIMIT 4;", sql);
+        let page = execute_query_paged(&pool, &limited, 3, 2).await.unwrap();
+        assert_eq!(first_column(&page), vec![3, 4]);
 
-    Ok(QueryResult {
-        columns,
-        rows: result_rows,
-        row_count,
-        execution_time_ms,
-    })
+        // Anything but a single SELECT runs as is, without paging.
+        let shown = execute_query_paged(&pool, "SHOW server_version", 3, 2)
+            .await
+            .unwrap();
+        assert_eq!(shown.row_count, 1);
+        assert_eq!(shown.total_estimate, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn syntax_errors_carry_code_and_position() {
+        let pool = test_pool().await;
+        let sql = "  SELECT 1 FORM t";
+        let errors = [
+            execute_query(&pool, sql).await.unwrap_err(),
+            execute_query_paged(&pool, sql, 10, 0).await.unwrap_err(),
+            execute_query_capped(&pool, sql, 10, None)
+                .await
+                .unwrap_err(),
+        ];
+        for error in errors {
+            match error {
+                AppError::Database { code, position, .. } => {
+                    assert_eq!(code.as_deref(), Some("42601"));
+                    // 1-based, pointing at "t" in the user's SQL, not the wrapper.
+                    assert_eq!(position, Some(17));
+                }
+                other => panic!("expected a database error, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a database in BESTGRES_TEST_DATABASE_URL"]
+    async fn dry_run_leaves_nothing_behind() {
+        let pool = test_pool().await;
+        let outcomes = dry_run_script(
+            &pool,
+            "CREATE TABLE bestgres_dry_run (a int); INSERT INTO bestgres_dry_run VALUES (1), (2)",
+        )
+        .await
+        .unwrap();
+        assert!(outcomes.iter().all(|o| o.success), "{:?}", outcomes);
+        assert_eq!(outcomes[1].rows_affected, Some(2));
+
+        let table: Option<String> =
+            sqlx::query_scalar("SELECT to_regclass('bestgres_dry_run')::text")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(table, None);
+    }
 }
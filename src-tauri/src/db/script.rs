@@ -0,0 +1,372 @@
+/// Split a SQL script into individual statements on top-level semicolons.
+/// Semicolons inside quoted strings, quoted identifiers, dollar-quoted bodies and
+/// comments don't split. Chunks holding only whitespace and comments are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut has_code = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            ';' => {
+                if has_code {
+                    statements.push(current.trim().to_string());
+                }
+                current.clear();
+                has_code = false;
+                i += 1;
+                continue;
+            }
+            '-' if next == Some('-') => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|&ch| ch == '\n')
+                    .map_or(chars.len(), |p| i + p);
+                current.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '/' if next == Some('*') => {
+                let end = block_comment_end(&chars, i);
+                current.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '\'' | '"' => {
+                // E'...' strings allow backslash escapes; everything else only doubles quotes.
+                let backslash_escapes = c == '\''
+                    && i > 0
+                    && matches!(chars[i - 1], 'e' | 'E')
+                    && (i < 2 || !is_ident_char(chars[i - 2]));
+                let end = quoted_end(&chars, i, backslash_escapes);
+                current.extend(&chars[i..end]);
+                has_code = true;
+                i = end;
+                continue;
+            }
+            '$' => {
+                if let Some(tag) = dollar_tag(&chars, i) {
+                    let end = dollar_quoted_end(&chars, i, &tag);
+                    current.extend(&chars[i..end]);
+                    has_code = true;
+                    i = end;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        if !c.is_whitespace() {
+            has_code = true;
+        }
+        current.push(c);
+        i += 1;
+    }
+
+    if has_code {
+        statements.push(current.trim().to_string());
+    }
+    statements
+}
+
+/// Reformat SQL with upper-case keywords and consistent indentation. String literals are
+/// left alone by the formatter; dollar-quoted bodies (function definitions, DO blocks) are
+/// swapped out for placeholder literals while it runs, so they come back byte for byte.
+pub fn format_sql(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut protected = String::new();
+    let mut bodies: Vec<(String, String)> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let end = match c {
+            '-' if next == Some('-') => chars[i..]
+                .iter()
+                .position(|&ch| ch == '\n')
+                .map_or(chars.len(), |p| i + p),
+            '/' if next == Some('*') => block_comment_end(&chars, i),
+            '\'' | '"' => {
+                let backslash_escapes = c == '\''
+                    && i > 0
+                    && matches!(chars[i - 1], 'e' | 'E')
+                    && (i < 2 || !is_ident_char(chars[i - 2]));
+                quoted_end(&chars, i, backslash_escapes)
+            }
+            '$' => match dollar_tag(&chars, i) {
+                Some(tag) => {
+                    let end = dollar_quoted_end(&chars, i, &tag);
+                    let placeholder = format!("'bestgres_dollar_quote_{}'", bodies.len());
+                    protected.push_str(&placeholder);
+                    bodies.push((placeholder, chars[i..end].iter().collect()));
+                    i = end;
+                    continue;
+                }
+                None => i + 1,
+            },
+            _ => i + 1,
+        };
+        protected.extend(&chars[i..end]);
+        i = end;
+    }
+
+    let options = sqlformat::FormatOptions {
+        uppercase: Some(true),
+        ..Default::default()
+    };
+    let mut formatted = sqlformat::format(&protected, &sqlformat::QueryParams::None, &options);
+    for (placeholder, body) in &bodies {
+        formatted = formatted.replacen(placeholder.as_str(), body, 1);
+    }
+    formatted
+}
+
+/// A bare word (keyword or unquoted identifier) of a statement, lowercased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub text: String,
+    /// Parenthesis nesting depth the word appears at.
+    pub depth: usize,
+    /// Whether the word is the first token inside its parentheses, e.g. `delete` in
+    /// `AS (DELETE ...)`.
+    pub opens_group: bool,
+}
+
+/// The bare words of a statement in order. Quoted strings and identifiers, dollar-quoted
+/// bodies and comments are skipped.
+pub fn words(sql: &str) -> Vec<Word> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut words = Vec::new();
+    let mut depth: usize = 0;
+    let mut opens_group = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            '-' if next == Some('-') => {
+                i = chars[i..]
+                    .iter()
+                    .position(|&ch| ch == '\n')
+                    .map_or(chars.len(), |p| i + p);
+                continue;
+            }
+            '/' if next == Some('*') => {
+                i = block_comment_end(&chars, i);
+                continue;
+            }
+            '\'' | '"' => {
+                let backslash_escapes = c == '\''
+                    && i > 0
+                    && matches!(chars[i - 1], 'e' | 'E')
+                    && (i < 2 || !is_ident_char(chars[i - 2]));
+                if backslash_escapes {
+                    // The `E` prefix was read as a word of its own.
+                    words.pop();
+                }
+                i = quoted_end(&chars, i, backslash_escapes);
+                opens_group = false;
+                continue;
+            }
+            '$' => {
+                if let Some(tag) = dollar_tag(&chars, i) {
+                    i = dollar_quoted_end(&chars, i, &tag);
+                    opens_group = false;
+                    continue;
+                }
+            }
+            '(' => {
+                depth += 1;
+                opens_group = true;
+                i += 1;
+                continue;
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                opens_group = false;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        if is_ident_char(c) {
+            let start = i;
+            while i < chars.len() && (is_ident_char(chars[i]) || chars[i] == '$') {
+                i += 1;
+            }
+            words.push(Word {
+                text: chars[start..i].iter().collect::<String>().to_lowercase(),
+                depth,
+                opens_group,
+            });
+            opens_group = false;
+            continue;
+        }
+
+        if !c.is_whitespace() {
+            opens_group = false;
+        }
+        i += 1;
+    }
+    words
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Index just past the quote that closes the string or identifier opened at `start`.
+/// An unterminated quote runs to the end of the script.
+fn quoted_end(chars: &[char], start: usize, backslash_escapes: bool) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        if backslash_escapes && chars[i] == '\\' {
+            i += 2;
+            continue;
+        }
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Index just past the `*/` closing the comment opened at `start`. Block comments nest.
+fn block_comment_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i + 1 < chars.len() {
+        match (chars[i], chars[i + 1]) {
+            ('/', '*') => {
+                depth += 1;
+                i += 2;
+            }
+            ('*', '/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+/// The opening `$tag$` at `start`, if there is one. `$1`-style parameters and `$` inside
+/// identifiers aren't dollar quotes.
+fn dollar_tag(chars: &[char], start: usize) -> Option<String> {
+    if start > 0 && is_ident_char(chars[start - 1]) {
+        return None;
+    }
+    let mut i = start + 1;
+    while i < chars.len() && chars[i] != '$' {
+        if !is_ident_char(chars[i]) || (i == start + 1 && chars[i].is_ascii_digit()) {
+            return None;
+        }
+        i += 1;
+    }
+    if i >= chars.len() {
+        return None;
+    }
+    Some(chars[start..=i].iter().collect())
+}
+
+/// Index just past the closing tag of the dollar-quoted body opened at `start`.
+fn dollar_quoted_end(chars: &[char], start: usize, tag: &str) -> usize {
+    let tag: Vec<char> = tag.chars().collect();
+    let mut i = start + tag.len();
+    while i + tag.len() <= chars.len() {
+        if chars[i..i + tag.len()] == tag[..] {
+            return i + tag.len();
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_on_top_level_semicolons_only() {
+        let sql = "SELECT ';' AS \"a;b\"; -- c;d\nSELECT E'\\';' ;\n\
+                   DO $x$ BEGIN PERFORM 1; END $x$;; /* e; */";
+        assert_eq!(
+            split_statements(sql),
+            [
+                "SELECT ';' AS \"a;b\"",
+                "-- c;d\nSELECT E'\\';'",
+                "DO $x$ BEGIN PERFORM 1; END $x$",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_keeps_parameters_and_unterminated_tails() {
+        assert_eq!(
+            split_statements("SELECT $1; SELECT 'open;"),
+            ["SELECT $1", "SELECT 'open;"]
+        );
+        assert!(split_statements(" ; -- only a comment\n").is_empty());
+    }
+
+    fn texts(sql: &str) -> Vec<String> {
+        words(sql).into_iter().map(|w| w.text).collect()
+    }
+
+    #[test]
+    fn words_skip_literals_and_comments() {
+        assert_eq!(
+            texts("SELECT 'drop' AS \"Delete\", $$update$$ -- insert\n/* merge */ FROM t"),
+            ["select", "as", "from", "t"]
+        );
+        assert_eq!(texts("select E'it\\'s', x"), ["select", "x"]);
+    }
+
+    #[test]
+    fn words_track_parenthesis_depth() {
+        let words = words("WITH d AS (DELETE FROM t) SELECT (1)");
+        let delete = words.iter().find(|w| w.text == "delete").unwrap();
+        assert_eq!(delete.depth, 1);
+        assert!(delete.opens_group);
+        let select = words.iter().find(|w| w.text == "select").unwrap();
+        assert_eq!(select.depth, 0);
+        assert!(!select.opens_group);
+    }
+
+    #[test]
+    fn format_sql_uppercases_keywords_but_not_literals() {
+        let formatted = format_sql("select a from t where b = 'select'");
+        assert!(formatted.starts_with("SELECT"), "{}", formatted);
+        assert!(formatted.contains("FROM"), "{}", formatted);
+        assert!(formatted.contains("'select'"), "{}", formatted);
+    }
+
+    #[test]
+    fn format_sql_keeps_dollar_quoted_bodies() {
+        let body = "$fn$\n  select  1 ;\n$fn$";
+        let sql = format!("create function f() returns int language sql as {}", body);
+        let formatted = format_sql(&sql);
+        assert!(formatted.starts_with("CREATE"), "{}", formatted);
+        assert!(formatted.contains(body), "{}", formatted);
+    }
+}
@@ -1,6 +1,9 @@
 mod commands;
 mod db;
 mod models;
+mod pgfiles;
+mod secrets;
+mod storage;
 
 use commands::connection::AppState;
 use tauri::Manager;
@@ -16,33 +19,108 @@ pub fn run() {
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.set_icon(APP_ICON.clone());
             }
+            commands::connection::spawn_heartbeat(&app.state::<AppState>());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::connection::add_connection,
             commands::connection::update_connection,
             commands::connection::remove_connection,
+            commands::connection::duplicate_connection,
+            commands::connection::export_connections,
+            commands::connection::import_connections,
+            commands::connection::reorder_connections,
+            commands::connection::parse_connection_uri,
+            commands::connection::load_pgpass_connections,
             commands::connection::connect,
             commands::connection::disconnect,
             commands::connection::check_connection,
+            commands::connection::test_connection_details,
+            commands::connection::validate_connection_config,
             commands::connection::list_connections,
+            commands::connection::password_backend,
             commands::connection::load_config_connections,
+            commands::cursor::open_cursor,
+            commands::cursor::fetch_cursor,
+            commands::cursor::close_cursor,
+            commands::notify::listen,
+            commands::notify::unlisten,
             commands::query::list_databases,
+            commands::query::list_database_sizes,
+            commands::query::get_current_role_info,
+            commands::query::get_server_info,
+            commands::query::list_active_sessions,
+            commands::query::list_blocking_locks,
+            commands::query::terminate_session,
+            commands::query::terminate_database_connections,
+            commands::query::cancel_backend,
             commands::query::get_schema,
+            commands::query::search_schema_objects,
+            commands::query::list_schemas,
+            commands::query::get_object_definition,
+            commands::query::refresh_materialized_view,
+            commands::query::maintain_table,
+            commands::query::list_enum_types,
+            commands::query::list_extensions,
+            commands::query::create_extension,
+            commands::query::get_sequence_info,
+            commands::query::get_table_row_estimate,
+            commands::query::get_table_size,
             commands::query::get_primary_key_columns,
             commands::query::get_columns,
+            commands::query::get_columns_bulk,
             commands::query::get_table_structure,
+            commands::query::diff_table_structure,
+            commands::query::get_table_ddl,
+            commands::query::format_sql,
             commands::query::execute_query,
+            commands::query::execute_query_params,
+            commands::query::execute_query_paged,
+            commands::query::browse_table,
+            commands::query::execute_script,
+            commands::query::dry_run_script,
+            commands::query::explain_query,
+            commands::query::estimate_query_cost,
+            commands::query::describe_query_sources,
             commands::query::update_cell,
+            commands::query::update_cells,
+            commands::query::bulk_update,
             commands::query::insert_row,
+            commands::query::duplicate_row,
+            commands::query::import_csv,
             commands::query::delete_rows,
+            commands::export::format_query_result,
+            commands::export::export_queries,
+            commands::export::export_query_csv,
+            commands::export::export_query_json,
+            commands::export::export_insert_sql,
+            commands::transaction::begin_transaction,
+            commands::transaction::commit_transaction,
+            commands::transaction::rollback_transaction,
+            commands::transaction::savepoint,
+            commands::transaction::rollback_to_savepoint,
+            commands::transaction::release_savepoint,
+            commands::transaction::list_savepoints,
             commands::history::add_to_history,
             commands::history::get_history,
             commands::history::clear_history,
             commands::history::save_query,
+            commands::history::update_saved_query,
             commands::history::list_saved_queries,
+            commands::history::list_saved_queries_by_tag,
+            commands::history::list_tags,
             commands::history::delete_saved_query,
+            commands::session::save_session,
+            commands::session::load_session,
+            commands::settings::get_settings,
+            commands::settings::update_settings,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                tauri::async_runtime::block_on(commands::connection::close_all_pools(&state));
+            }
+        });
 }
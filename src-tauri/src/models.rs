@@ -1,4 +1,51 @@
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgDatabaseError, PgErrorPosition};
+
+/// PostgreSQL `sslmode` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl SslMode {
+    /// The value used for the `sslmode` connection parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        }
+    }
+
+    /// Parse an `sslmode` parameter value. libpq's `allow` (try without TLS first) has no
+    /// exact equivalent here and maps to `prefer`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "disable" => Some(SslMode::Disable),
+            "allow" | "prefer" => Some(SslMode::Prefer),
+            "require" => Some(SslMode::Require),
+            "verify-ca" => Some(SslMode::VerifyCa),
+            "verify-full" => Some(SslMode::VerifyFull),
+            _ => None,
+        }
+    }
+
+    /// Mapping for configs written before `ssl_mode` existed.
+    pub fn from_legacy_flag(ssl: bool) -> Self {
+        if ssl {
+            SslMode::Require
+        } else {
+            SslMode::Disable
+        }
+    }
+}
 
 /// Metadata for a saved database connection (passwords stored in system keychain).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,22 +56,102 @@ pub struct ConnectionConfig {
     pub port: u16,
     pub user: String,
     pub database: String,
-    /// Whether to use SSL for the connection.
+    /// Legacy on/off SSL flag; only used when `ssl_mode` is absent.
+    #[serde(default)]
     pub ssl: bool,
+    #[serde(default)]
+    pub ssl_mode: Option<SslMode>,
+    /// Path to a root CA certificate used to verify the server (verify-ca / verify-full).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssl_root_cert: Option<String>,
+    /// Position in the connection list; connections without one sort last.
+    #[serde(default = "default_order")]
+    pub order: i32,
+    /// `application_name` reported to the server; the settings default is used when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    /// Block edits from the app and open every session with
+    /// `default_transaction_read_only = on`.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Pool size; 5 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u32>,
+    /// Seconds to wait for a pooled connection (including connecting); 5 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquire_timeout_secs: Option<u64>,
+    /// Database other than `database` that was most recently opened, so the UI can reselect
+    /// it on reconnect. Absent until one is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_database: Option<String>,
+}
+
+impl ConnectionConfig {
+    /// Effective SSL mode, falling back to the legacy `ssl` flag.
+    pub fn ssl_mode(&self) -> SslMode {
+        self.ssl_mode.unwrap_or_else(|| SslMode::from_legacy_flag(self.ssl))
+    }
+
+    /// Whether `host` is a Unix socket directory (e.g. /var/run/postgresql) rather than a
+    /// host name.
+    pub fn is_unix_socket(&self) -> bool {
+        self.host.starts_with('/')
+    }
+}
+
+/// A new, unsaved connection config and its password, e.g. parsed from a connection URI
+/// or a pgpass file. Saving it through `add_connection` moves the password to the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionDraft {
+    pub config: ConnectionConfig,
+    pub password: String,
+}
+
+/// Outcome of loading the connection config files: the connections that loaded, plus a
+/// `(name, error)` pair for each file or connection that had a problem. Connections whose
+/// keychain write or pool creation failed are still loaded and also reported here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionLoadResult {
+    pub connections: Vec<ConnectionConfig>,
+    pub errors: Vec<(String, String)>,
 }
 
 /// Config format for JSON files in ~/.config/bestgres/connections/.
-/// Includes password directly (unlike ConnectionConfig which uses keychain).
+/// The password lives in the keychain under `id`. Older files have no id and a plaintext
+/// `password`; they are migrated to the keychain and rewritten on load.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionFileConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub name: String,
     pub host: String,
     pub port: u16,
     pub user: String,
-    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
     pub database: String,
     #[serde(default)]
     pub ssl: bool,
+    #[serde(default)]
+    pub ssl_mode: Option<SslMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssl_root_cert: Option<String>,
+    #[serde(default = "default_order")]
+    pub order: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_name: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquire_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_database: Option<String>,
+}
+
+fn default_order() -> i32 {
+    i32::MAX
 }
 
 /// Information about a single table/view in the schema.
@@ -40,7 +167,70 @@ pub struct SchemaObject {
 pub enum SchemaObjectType {
     Table,
     View,
+    MaterializedView,
     Function,
+    Sequence,
+}
+
+impl SchemaObjectType {
+    /// The serialized (snake_case) name, e.g. "materialized_view".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaObjectType::Table => "table",
+            SchemaObjectType::View => "view",
+            SchemaObjectType::MaterializedView => "materialized_view",
+            SchemaObjectType::Function => "function",
+            SchemaObjectType::Sequence => "sequence",
+        }
+    }
+}
+
+/// Current state and parameters of a sequence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceInfo {
+    pub schema: String,
+    pub name: String,
+    pub data_type: String,
+    pub start_value: i64,
+    pub increment: i64,
+    pub min_value: i64,
+    pub max_value: i64,
+    /// None until nextval() has been called (or without SELECT privilege).
+    pub last_value: Option<i64>,
+    pub is_cycled: bool,
+}
+
+/// On-disk size of a table in bytes. `total_bytes` also counts TOAST data, so it can
+/// exceed `table_bytes + index_bytes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSize {
+    pub total_bytes: i64,
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+}
+
+/// On-disk size of a database in bytes (`pg_database_size`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSize {
+    pub name: String,
+    pub bytes: i64,
+}
+
+/// An installed extension (pg_extension).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionInfo {
+    pub name: String,
+    pub version: String,
+    /// Schema holding the extension's objects.
+    pub schema: String,
+}
+
+/// An enum type and its labels in sort order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnumType {
+    pub schema: String,
+    pub name: String,
+    pub values: Vec<String>,
 }
 
 /// A column in a table.
@@ -50,6 +240,9 @@ pub struct ColumnInfo {
     pub data_type: String,
     pub is_nullable: bool,
     pub is_primary_key: bool,
+    /// Generated column (`GENERATED ALWAYS AS (...)`); can't be written to.
+    #[serde(default)]
+    pub is_generated: bool,
 }
 
 /// Detailed column info for DDL/structure view.
@@ -58,7 +251,25 @@ pub struct ColumnDetail {
     pub name: String,
     pub data_type: String,
     pub is_nullable: bool,
+    /// The column default for display; `None` for sequence defaults, which
+    /// `is_auto_increment` covers.
     pub default_value: Option<String>,
+    /// The default exactly as in `information_schema.columns.column_default`.
+    #[serde(default)]
+    pub raw_default: Option<String>,
+    /// Identity column, or a default drawing from a sequence (serial columns).
+    #[serde(default)]
+    pub is_auto_increment: bool,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// ALWAYS or BY DEFAULT for identity columns.
+    #[serde(default)]
+    pub identity_generation: Option<String>,
+    /// Expression of a generated (STORED) column.
+    #[serde(default)]
+    pub generation_expression: Option<String>,
+    #[serde(default)]
+    pub is_generated: bool,
 }
 
 /// Index info for structure view.
@@ -88,6 +299,17 @@ pub struct ForeignKeyInfo {
     pub ref_column: String,
 }
 
+/// Trigger info for structure view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerInfo {
+    pub name: String,
+    /// BEFORE, AFTER, or INSTEAD OF.
+    pub timing: String,
+    /// INSERT, UPDATE, DELETE and/or TRUNCATE.
+    pub events: Vec<String>,
+    pub definition: String,
+}
+
 /// Full table structure for the DDL view.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableStructure {
@@ -95,15 +317,297 @@ pub struct TableStructure {
     pub indexes: Vec<IndexInfo>,
     pub constraints: Vec<ConstraintInfo>,
     pub foreign_keys: Vec<ForeignKeyInfo>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerInfo>,
+    #[serde(default)]
+    pub table_comment: Option<String>,
+}
+
+/// An item present in both tables of a `SchemaDiff` under the same name, but different.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemChange<T> {
+    pub name: String,
+    /// As in the first table.
+    pub before: T,
+    /// As in the second table.
+    pub after: T,
+}
+
+/// Differences between two tables' structures, going from the first table to the second:
+/// "added" items exist only in the second, "removed" ones only in the first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    pub added_columns: Vec<ColumnDetail>,
+    pub removed_columns: Vec<ColumnDetail>,
+    /// Same name but a different type, nullability or default.
+    pub changed_columns: Vec<ItemChange<ColumnDetail>>,
+    pub added_indexes: Vec<IndexInfo>,
+    pub removed_indexes: Vec<IndexInfo>,
+    pub changed_indexes: Vec<ItemChange<IndexInfo>>,
+    pub added_constraints: Vec<ConstraintInfo>,
+    pub removed_constraints: Vec<ConstraintInfo>,
+    pub changed_constraints: Vec<ItemChange<ConstraintInfo>>,
+}
+
+/// One cell change in a batch update. A null `new_value` sets the cell to NULL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellEdit {
+    pub column: String,
+    pub primary_key_columns: Vec<String>,
+    pub primary_key_values: Vec<serde_json::Value>,
+    pub new_value: serde_json::Value,
+}
+
+/// Comparison operator for a structured column filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Neq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Like,
+    IsNull,
+    IsNotNull,
+}
+
+/// Maintenance operation run on a table by `maintain_table`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceOp {
+    Vacuum,
+    /// Rewrites the whole table; holds an ACCESS EXCLUSIVE lock while it runs.
+    VacuumFull,
+    Analyze,
+    Reindex,
+}
+
+impl MaintenanceOp {
+    /// The statement keyword(s), followed by the table name.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            MaintenanceOp::Vacuum => "VACUUM",
+            MaintenanceOp::VacuumFull => "VACUUM FULL",
+            MaintenanceOp::Analyze => "ANALYZE",
+            MaintenanceOp::Reindex => "REINDEX TABLE",
+        }
+    }
+}
+
+/// A `column <op> value` condition built into a WHERE clause with the value bound
+/// as a parameter. `value` is ignored for IsNull / IsNotNull.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnFilter {
+    pub column: String,
+    pub op: FilterOp,
+    #[serde(default)]
+    pub value: serde_json::Value,
 }
 
 /// Result of executing a query — column names + rows of string values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
+    /// Server type name of each column, parallel to `columns` (e.g. "TIMESTAMPTZ").
+    #[serde(default)]
+    pub column_types: Vec<String>,
     pub rows: Vec<Vec<serde_json::Value>>,
     pub row_count: usize,
     pub execution_time_ms: u64,
+    /// Planner's estimate of the total rows for a paged SELECT; None otherwise.
+    #[serde(default)]
+    pub total_estimate: Option<i64>,
+    /// Rows inserted/updated/deleted by a statement that returned no result columns.
+    #[serde(default)]
+    pub rows_affected: Option<u64>,
+    /// Set when a `max_rows` cap cut the result short.
+    #[serde(default)]
+    pub truncated: bool,
+    /// Whether the rows can be edited in place: set by `execute_query` for a plain SELECT
+    /// of one table's columns that includes its whole primary key.
+    #[serde(default)]
+    pub editable: bool,
+    /// Primary key of the source table when `editable`.
+    #[serde(default)]
+    pub primary_key_columns: Vec<String>,
+    #[serde(default)]
+    pub source_schema: Option<String>,
+    #[serde(default)]
+    pub source_table: Option<String>,
+}
+
+/// Outcome of one statement of a `dry_run_script`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementOutcome {
+    pub statement: String,
+    pub success: bool,
+    /// Rows inserted/updated/deleted; None for statements that return rows, or failed.
+    pub rows_affected: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Planner's estimate for a statement's root plan node, in the planner's arbitrary cost
+/// units.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub total_cost: f64,
+    pub plan_rows: f64,
+}
+
+/// Results of running a multi-statement script, one per statement that completed.
+/// If a statement failed, `error` holds its message and it is statement number
+/// `results.len() + 1`; nothing after it was run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptResult {
+    pub results: Vec<QueryResult>,
+    pub error: Option<String>,
+}
+
+/// Where a result column of an ad-hoc query came from.
+/// Source fields are None for computed columns (expressions, aggregates, literals).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSource {
+    pub name: String,
+    pub data_type: String,
+    pub source_schema: Option<String>,
+    pub source_table: Option<String>,
+    pub source_column: Option<String>,
+}
+
+/// A role the current user belongs to, directly or through inherited membership.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleMembership {
+    pub name: String,
+    /// True when the membership is granted directly to the current user.
+    pub is_direct: bool,
+    pub is_superuser: bool,
+    pub can_create_role: bool,
+    pub can_create_db: bool,
+}
+
+/// Who the connection is acting as and what that role can do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleInfo {
+    pub current_user: String,
+    pub session_user: String,
+    pub is_superuser: bool,
+    pub can_create_role: bool,
+    pub can_create_db: bool,
+    pub member_of: Vec<RoleMembership>,
+}
+
+/// Server version and a few settings that affect what the server supports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerInfo {
+    /// Full `version()` string.
+    pub version: String,
+    /// `server_version_num`, e.g. 160002 for 16.2.
+    pub version_num: i32,
+    pub encoding: String,
+    pub timezone: String,
+    pub max_connections: i32,
+}
+
+/// A backend from pg_stat_activity. Without pg_read_all_stats (or superuser), other
+/// users' `query` reads "<insufficient privilege>" and their `state` is null.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub pid: i32,
+    pub usename: Option<String>,
+    pub datname: Option<String>,
+    pub application_name: Option<String>,
+    pub client_addr: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+    /// RFC 3339 start time of the current or last query.
+    pub query_start: Option<String>,
+    /// Whether this is the session making the request.
+    pub is_current: bool,
+}
+
+/// A session waiting on a lock held (or also requested) by another. As with
+/// `SessionInfo`, other users' queries read "<insufficient privilege>" without
+/// pg_read_all_stats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub blocked_pid: i32,
+    pub blocked_user: Option<String>,
+    pub blocked_query: Option<String>,
+    /// RFC 3339 start time of the blocked query.
+    pub blocked_since: Option<String>,
+    pub blocking_pid: i32,
+    pub blocking_user: Option<String>,
+    pub blocking_query: Option<String>,
+    /// e.g. "idle in transaction" for a session holding locks between statements.
+    pub blocking_state: Option<String>,
+    /// Type of the awaited lock, e.g. "relation", "transactionid", "tuple".
+    pub lock_type: Option<String>,
+    /// Requested lock mode, e.g. "ShareLock" or "AccessExclusiveLock".
+    pub lock_mode: Option<String>,
+    /// Locked relation for relation-level locks.
+    pub relation: Option<String>,
+}
+
+/// Payload of the `pg-notification` event emitted for each NOTIFY received by `listen`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PgNotification {
+    pub connection_id: String,
+    pub channel: String,
+    pub payload: String,
+    /// Pid of the backend that sent the notification.
+    pub process_id: u32,
+}
+
+/// Broad kind of a SQL statement, from its leading keyword (see `classify_statement`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementKind {
+    /// SELECT, VALUES or TABLE, including WITH queries whose CTEs only read.
+    Select,
+    /// INSERT, UPDATE, DELETE or MERGE, or a WITH query containing one.
+    Dml,
+    /// CREATE, ALTER, DROP, TRUNCATE, COMMENT, GRANT, REVOKE, and SELECT ... INTO.
+    Ddl,
+    /// Anything else: SET, SHOW, EXPLAIN, COPY, VACUUM, transaction control, ...
+    Utility,
+}
+
+/// Where connection passwords are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// The OS keyring (Keychain, Credential Manager, Secret Service).
+    Keyring,
+    /// An encrypted file in the config directory, used when no keyring is available.
+    File,
+}
+
+/// Output format for exported query results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+    Markdown,
+}
+
+/// Layout of a JSON export: one array of row objects, or one object per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonExportFormat {
+    Array,
+    Jsonl,
+}
+
+/// Per-query outcome of a multi-query export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSectionResult {
+    pub label: String,
+    pub row_count: usize,
+    pub error: Option<String>,
 }
 
 /// A single entry in query history.
@@ -111,7 +615,95 @@ pub struct QueryResult {
 pub struct HistoryEntry {
     pub sql: String,
     pub database: String,
+    /// Connection the query ran on; None for entries recorded before this was tracked.
+    #[serde(default)]
+    pub connection_id: Option<String>,
     pub executed_at: String,
+    /// Execution metadata; entries recorded before these fields existed load with no
+    /// row count or timing and count as successful.
+    #[serde(default)]
+    pub row_count: Option<usize>,
+    #[serde(default)]
+    pub execution_time_ms: Option<u64>,
+    #[serde(default = "default_true")]
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An open query tab, saved in session.json so tabs survive a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabState {
+    pub connection_id: String,
+    pub database: String,
+    pub sql: String,
+    pub title: String,
+}
+
+/// User settings stored in settings.json in the bestgres config directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    /// Number of history entries to keep; 0 keeps everything.
+    #[serde(default = "default_max_history")]
+    pub max_history: usize,
+    /// `application_name` sent on connect (shown in pg_stat_activity) unless a connection
+    /// sets its own.
+    #[serde(default = "default_application_name")]
+    pub application_name: String,
+    /// Most pools kept open before idle secondary-database pools are closed, least recently
+    /// used first; 0 keeps everything.
+    #[serde(default = "default_max_pools")]
+    pub max_pools: usize,
+    /// Seconds between background liveness checks of each connection; 0 turns them off.
+    #[serde(default = "default_heartbeat_interval_secs")]
+    pub heartbeat_interval_secs: u64,
+    /// Extra attempts after a transient connection failure (timeout, refused, reset).
+    #[serde(default = "default_connect_retries")]
+    pub connect_retries: u32,
+    /// Wait before the first retry; doubled for each one after.
+    #[serde(default = "default_connect_retry_backoff_ms")]
+    pub connect_retry_backoff_ms: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_history: default_max_history(),
+            application_name: default_application_name(),
+            max_pools: default_max_pools(),
+            heartbeat_interval_secs: default_heartbeat_interval_secs(),
+            connect_retries: default_connect_retries(),
+            connect_retry_backoff_ms: default_connect_retry_backoff_ms(),
+        }
+    }
+}
+
+fn default_max_history() -> usize {
+    200
+}
+
+fn default_application_name() -> String {
+    "bestgres".to_string()
+}
+
+fn default_max_pools() -> usize {
+    20
+}
+
+fn default_heartbeat_interval_secs() -> u64 {
+    30
+}
+
+fn default_connect_retries() -> u32 {
+    2
+}
+
+fn default_connect_retry_backoff_ms() -> u64 {
+    500
 }
 
 /// A saved / favorite query.
@@ -121,13 +713,36 @@ pub struct SavedQuery {
     pub name: String,
     pub sql: String,
     pub database: String,
+    /// RFC 3339 timestamps; None for queries saved before they were tracked.
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Order for `list_saved_queries`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavedQuerySort {
+    /// Alphabetical, case-insensitive.
+    Name,
+    /// Most recently updated first; queries without a timestamp last.
+    UpdatedAt,
 }
 
 /// Errors returned to the frontend as user-friendly strings.
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
-    #[error("Database error: {0}")]
-    Database(String),
+    /// `code` is the SQLSTATE and `position` the 1-based character offset of the error in
+    /// the statement, when Postgres reports them.
+    #[error("Database error: {message}")]
+    Database {
+        message: String,
+        code: Option<String>,
+        position: Option<usize>,
+    },
 
     #[error("Connection error: {0}")]
     Connection(String),
@@ -137,14 +752,106 @@ pub enum AppError {
 
     #[error("Keychain error: {0}")]
     Keychain(String),
+
+    /// An edit or admin action refused locally because the connection is marked read-only.
+    #[error("Connection is read-only")]
+    ReadOnly,
 }
 
-// Allow AppError to be returned from Tauri commands as a serialized string.
+impl AppError {
+    /// A database error with only a message.
+    pub fn database(message: String) -> Self {
+        AppError::Database {
+            message,
+            code: None,
+            position: None,
+        }
+    }
+
+    /// A database error from sqlx, keeping the SQLSTATE and error position when the server
+    /// sent them.
+    pub fn from_sqlx(e: sqlx::Error) -> Self {
+        let pg_error = e
+            .as_database_error()
+            .and_then(|db| db.try_downcast_ref::<PgDatabaseError>());
+        let code = pg_error.map(|pg| pg.code().to_string());
+        let position = pg_error.and_then(|pg| match pg.position()? {
+            PgErrorPosition::Original(position) => Some(position),
+            // Inside a query generated by a function; it doesn't point into the user's SQL.
+            PgErrorPosition::Internal { .. } => None,
+        });
+        AppError::Database {
+            message: e.to_string(),
+            code,
+            position,
+        }
+    }
+}
+
+// Allow AppError to be returned from Tauri commands as `{ kind, message, code, position }`.
+// `kind` names the variant, so e.g. a read-only refusal can be told apart from a server
+// error; `message` is the full display string; `code` and `position` are only set for
+// database errors that carry them.
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        use serde::ser::SerializeStruct;
+
+        let (code, position) = match self {
+            AppError::Database { code, position, .. } => (code.as_deref(), *position),
+            _ => (None, None),
+        };
+        let kind = match self {
+            AppError::Database { .. } => "database",
+            AppError::Connection(_) => "connection",
+            AppError::Config(_) => "config",
+            AppError::Keychain(_) => "keychain",
+            AppError::ReadOnly => "read_only",
+        };
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("kind", kind)?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("code", &code)?;
+        state.serialize_field("position", &position)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn read_only_error_has_its_own_kind_and_no_code() {
+        assert_eq!(
+            serde_json::to_value(AppError::ReadOnly).unwrap(),
+            json!({
+                "kind": "read_only",
+                "message": "Connection is read-only",
+                "code": null,
+                "position": null,
+            })
+        );
+    }
+
+    #[test]
+    fn database_error_keeps_code_and_position() {
+        let err = AppError::Database {
+            message: "syntax error".into(),
+            code: Some("42601".into()),
+            position: Some(8),
+        };
+        assert_eq!(
+            serde_json::to_value(err).unwrap(),
+            json!({
+                "kind": "database",
+                "message": "Database error: syntax error",
+                "code": "42601",
+                "position": 8,
+            })
+        );
     }
 }
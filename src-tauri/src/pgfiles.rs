@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// One `host:port:database:username:password` line of a pgpass file.
+/// Any of the first four fields may be `*`, matching anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgpassEntry {
+    pub host: String,
+    pub port: String,
+    pub database: String,
+    pub user: String,
+    pub password: String,
+}
+
+impl PgpassEntry {
+    /// Whether this line applies to a connection, honouring `*` wildcards.
+    pub fn matches(&self, host: &str, port: u16, database: &str, user: &str) -> bool {
+        let field_matches = |pattern: &str, value: &str| pattern == "*" || pattern == value;
+        field_matches(&self.host, host)
+            && field_matches(&self.port, &port.to_string())
+            && field_matches(&self.database, database)
+            && field_matches(&self.user, user)
+    }
+}
+
+/// Parse a pgpass file. Blank lines and `#` comments are ignored, `\:` and `\\` are
+/// unescaped, and lines without exactly five fields are skipped.
+pub fn parse_pgpass(text: &str) -> Vec<PgpassEntry> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let mut fields = Vec::with_capacity(5);
+        let mut field = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => field.extend(chars.next()),
+                ':' => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+        fields.push(field);
+
+        let [host, port, database, user, password]: [String; 5] = match fields.try_into() {
+            Ok(fields) => fields,
+            Err(_) => continue,
+        };
+        if port != "*" && port.parse::<u16>().is_err() {
+            continue;
+        }
+        entries.push(PgpassEntry {
+            host,
+            port,
+            database,
+            user,
+            password,
+        });
+    }
+    entries
+}
+
+/// Parse a pg_service.conf file into `(service name, key/value settings)` in file order.
+/// Settings before the first `[section]` and lines that aren't `key=value` are skipped.
+pub fn parse_service_file(text: &str) -> Vec<(String, HashMap<String, String>)> {
+    let mut services: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            services.push((name.trim().to_string(), HashMap::new()));
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if let Some((_, settings)) = services.last_mut() {
+            settings.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    services
+}
+
+/// Location of the pgpass file: `$PGPASSFILE`, else `~/.pgpass`
+/// (`%APPDATA%\postgresql\pgpass.conf` on Windows).
+pub fn pgpass_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("PGPASSFILE") {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(windows) {
+        dirs::config_dir().map(|d| d.join("postgresql").join("pgpass.conf"))
+    } else {
+        dirs::home_dir().map(|d| d.join(".pgpass"))
+    }
+}
+
+/// Location of the per-user service file: `$PGSERVICEFILE`, else `~/.pg_service.conf`.
+pub fn service_file_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("PGSERVICEFILE") {
+        return Some(PathBuf::from(path));
+    }
+    dirs::home_dir().map(|d| d.join(".pg_service.conf"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pgpass_skips_comments_and_unescapes() {
+        let text = "# local dev\r\n\
+                    \n\
+                    *:5432:*:app:s3cr\\:et\\\\\r\n\
+                    db.example.com:*:reports:ro:pw\n\
+                    too:few:fields\n\
+                    host:notaport:db:user:pw\n";
+        let entries = parse_pgpass(text);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].host, "*");
+        assert_eq!(entries[0].password, "s3cr:et\\");
+        assert_eq!(entries[1].port, "*");
+    }
+
+    #[test]
+    fn pgpass_wildcards_match_anything() {
+        let entry = &parse_pgpass("*:5432:*:app:pw")[0];
+        assert!(entry.matches("db.example.com", 5432, "orders", "app"));
+        assert!(!entry.matches("db.example.com", 5433, "orders", "app"));
+        assert!(!entry.matches("db.example.com", 5432, "orders", "admin"));
+    }
+}
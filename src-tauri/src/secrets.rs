@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::{Digest, Sha256};
+
+use crate::commands::history::bestgres_dir;
+use crate::models::{AppError, SecretBackend};
+use crate::storage;
+
+const SERVICE: &str = "bestgres";
+
+/// Environment variable holding a user-chosen key for the password file. Without it the
+/// key is derived from the machine id and user name, which keeps the file unreadable on
+/// other machines but not from other programs running as the same user.
+const FILE_KEY_ENV: &str = "BESTGRES_PASSWORD_KEY";
+
+/// Set once the OS keyring has failed as unavailable, so later calls go straight to the
+/// file instead of waiting on it again.
+static KEYRING_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// The backend passwords are currently stored in. Probes the keyring with a lookup if it
+/// hasn't been used yet.
+pub fn backend() -> SecretBackend {
+    if !KEYRING_UNAVAILABLE.load(Ordering::Relaxed) {
+        let probe =
+            keyring::Entry::new(SERVICE, "bestgres-probe").and_then(|entry| entry.get_password());
+        if probe.as_ref().is_err_and(is_unavailable) {
+            mark_unavailable();
+        }
+    }
+    if KEYRING_UNAVAILABLE.load(Ordering::Relaxed) {
+        SecretBackend::File
+    } else {
+        SecretBackend::Keyring
+    }
+}
+
+/// Errors meaning there is no usable keyring at all (e.g. no Secret Service on a headless
+/// Linux box), as opposed to a problem with this one entry.
+fn is_unavailable(e: &keyring::Error) -> bool {
+    matches!(
+        e,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+fn mark_unavailable() {
+    KEYRING_UNAVAILABLE.store(true, Ordering::Relaxed);
+}
+
+/// Store a password in the OS keyring, or in the encrypted password file when there is no
+/// usable keyring.
+pub fn store_password(connection_id: &str, password: &str) -> Result<(), AppError> {
+    store_password_in(connection_id, password, password_file_path)
+}
+
+/// `store_password` with the fallback file at `file_path()`.
+fn store_password_in(
+    connection_id: &str,
+    password: &str,
+    file_path: impl FnOnce() -> Result<PathBuf, AppError>,
+) -> Result<(), AppError> {
+    if !KEYRING_UNAVAILABLE.load(Ordering::Relaxed) {
+        let result = keyring::Entry::new(SERVICE, connection_id)
+            .and_then(|entry| entry.set_password(password));
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if is_unavailable(&e) => mark_unavailable(),
+            Err(e) => return Err(AppError::Keychain(e.to_string())),
+        }
+    }
+
+    let path = file_path()?;
+    let mut passwords = read_password_file(&path)?;
+    passwords.insert(connection_id.to_string(), password.to_string());
+    write_password_file(&path, &passwords)
+}
+
+/// Read a password from the OS keyring, falling back to the encrypted password file when
+/// the keyring is unavailable or has no entry (e.g. it was stored while the keyring was
+/// down). `None` means no password is stored anywhere.
+pub fn get_password(connection_id: &str) -> Result<Option<String>, AppError> {
+    get_password_in(connection_id, password_file_path)
+}
+
+/// `get_password` with the fallback file at `file_path()`.
+fn get_password_in(
+    connection_id: &str,
+    file_path: impl FnOnce() -> Result<PathBuf, AppError>,
+) -> Result<Option<String>, AppError> {
+    if !KEYRING_UNAVAILABLE.load(Ordering::Relaxed) {
+        let result =
+            keyring::Entry::new(SERVICE, connection_id).and_then(|entry| entry.get_password());
+        match result {
+            Ok(password) => return Ok(Some(password)),
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) if is_unavailable(&e) => mark_unavailable(),
+            Err(e) => return Err(AppError::Keychain(e.to_string())),
+        }
+    }
+
+    Ok(read_password_file(&file_path()?)?.remove(connection_id))
+}
+
+fn password_file_path() -> Result<PathBuf, AppError> {
+    Ok(bestgres_dir()?.join("passwords.enc"))
+}
+
+/// Key for the password file: SHA-256 of `BESTGRES_PASSWORD_KEY` if set, otherwise of the
+/// machine id and user name.
+fn file_key() -> Key {
+    let material = std::env::var(FILE_KEY_ENV).unwrap_or_else(|_| {
+        let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_default();
+        format!("{}:{}:{}", SERVICE, machine_id.trim(), user)
+    });
+    Key::from(<[u8; 32]>::from(Sha256::digest(material.as_bytes())))
+}
+
+/// Decrypt the password file into connection id -> password. A missing file is empty.
+/// Each value is base64 of a 12-byte nonce followed by the ChaCha20-Poly1305 ciphertext.
+fn read_password_file(path: &Path) -> Result<HashMap<String, String>, AppError> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Ok(HashMap::new());
+    };
+    let encrypted: HashMap<String, String> = serde_json::from_str(&content)
+        .map_err(|e| AppError::Keychain(format!("Invalid password file: {}", e)))?;
+
+    let cipher = ChaCha20Poly1305::new(&file_key());
+    let undecryptable = || {
+        AppError::Keychain(format!(
+            "Cannot decrypt {}; was it written with a different {}?",
+            path.display(),
+            FILE_KEY_ENV
+        ))
+    };
+    encrypted
+        .into_iter()
+        .map(|(id, value)| {
+            let bytes = BASE64.decode(value).map_err(|_| undecryptable())?;
+            if bytes.len() < 12 {
+                return Err(undecryptable());
+            }
+            let (nonce, ciphertext) = bytes.split_at(12);
+            let plaintext = cipher
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| undecryptable())?;
+            let password = String::from_utf8(plaintext).map_err(|_| undecryptable())?;
+            Ok((id, password))
+        })
+        .collect()
+}
+
+fn write_password_file(path: &Path, passwords: &HashMap<String, String>) -> Result<(), AppError> {
+    let cipher = ChaCha20Poly1305::new(&file_key());
+    let mut encrypted = HashMap::with_capacity(passwords.len());
+    for (id, password) in passwords {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, password.as_bytes())
+            .map_err(|e| AppError::Keychain(format!("Cannot encrypt password: {}", e)))?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend(ciphertext);
+        encrypted.insert(id.clone(), BASE64.encode(bytes));
+    }
+
+    let json = serde_json::to_string_pretty(&encrypted)
+        .map_err(|e| AppError::Keychain(format!("Cannot serialize password file: {}", e)))?;
+    storage::write_atomic(path, json.as_bytes())
+        .map_err(|e| AppError::Keychain(format!("Cannot write password file: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("bestgres-{}.enc", uuid::Uuid::new_v4()));
+        assert!(read_password_file(&path).unwrap().is_empty());
+
+        let passwords = HashMap::from([
+            ("a".to_string(), "s3cret".to_string()),
+            ("b".to_string(), String::new()),
+        ]);
+        write_password_file(&path, &passwords).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("s3cret"));
+        assert_eq!(read_password_file(&path).unwrap(), passwords);
+
+        // A value that fails authentication is reported rather than returned.
+        let mut encrypted: HashMap<String, String> = serde_json::from_str(&content).unwrap();
+        let mut bytes = BASE64.decode(&encrypted["a"]).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        encrypted.insert("a".into(), BASE64.encode(bytes));
+        std::fs::write(&path, serde_json::to_string(&encrypted).unwrap()).unwrap();
+        assert!(read_password_file(&path).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn passwords_fall_back_to_the_file_without_a_keyring() {
+        // What a headless Linux box without Secret Service reports.
+        let failure = keyring::Error::PlatformFailure("no secret service".into());
+        assert!(is_unavailable(&failure));
+        mark_unavailable();
+        assert_eq!(backend(), SecretBackend::File);
+
+        let path = std::env::temp_dir().join(format!("bestgres-{}.enc", uuid::Uuid::new_v4()));
+        let file = || Ok(path.clone());
+        store_password_in("a", "s3cret", file).unwrap();
+        store_password_in("b", "other", file).unwrap();
+
+        assert_eq!(get_password_in("a", file).unwrap(), Some("s3cret".into()));
+        assert_eq!(get_password_in("b", file).unwrap(), Some("other".into()));
+        assert_eq!(get_password_in("c", file).unwrap(), None);
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("s3cret"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::Write;
+use std::path::Path;
+
+/// Replace `path` with `contents` atomically: write a temp file in the same directory,
+/// flush it to disk, then rename it over the target. A crash or failed write leaves the
+/// previous file intact instead of a truncated one.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+/// `write_atomic` with the temp file's contents written by `write`.
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut std::fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_write_keeps_the_previous_file() {
+        let dir = std::env::temp_dir().join(format!("bestgres-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        write_atomic(&path, b"{\"old\": true}").unwrap();
+
+        let err = write_atomic_with(&path, |file| {
+            file.write_all(b"{\"new\":")?;
+            Err(std::io::Error::other("disk full"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"old\": true}");
+        // The half-written temp file is cleaned up.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        write_atomic(&path, b"{\"new\": true}").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"new\": true}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
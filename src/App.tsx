@@ -8,7 +8,7 @@ import {
 } from "@/components/ConnectionDialog";
 import { ToastProvider, useToast } from "@/components/Toast";
 import { useTheme } from "@/hooks/use-theme";
-import type { Tab, ConnectionEntry } from "@/types";
+import type { Tab, ConnectionEntry, ConnectionLoadResult } from "@/types";
 
 function App() {
   return (
@@ -33,17 +33,22 @@ function AppInner() {
   useEffect(() => {
     async function loadFromConfig() {
       try {
-        const loaded = await invoke<ConnectionEntry[]>("load_config_connections");
+        const { connections: loaded, errors } =
+          await invoke<ConnectionLoadResult>("load_config_connections");
         if (loaded.length > 0) {
           setConnections(loaded);
           setActiveConnectionId(loaded[0].id);
         }
+        if (errors.length > 0) {
+          const details = errors.map(([name, error]) => `${name}: ${error}`).join("; ");
+          toast("error", `${loaded.length} connections loaded, ${errors.length} failed: ${details}`);
+        }
       } catch {
         // Config dir may not exist or have no files — that's fine
       }
     }
     loadFromConfig();
-  }, []);
+  }, [toast]);
 
   /* ── Tab management ── */
 
@@ -108,18 +113,17 @@ function AppInner() {
 
   const handleAddConnection = useCallback(async (data: ConnectionFormData) => {
     const id = crypto.randomUUID();
-    await invoke("add_connection", {
-      config: {
-        id,
-        name: data.name,
-        host: data.host,
-        port: data.port,
-        user: data.user,
-        database: data.database,
-        ssl: data.ssl,
-      },
-      password: data.password,
-    });
+    const config = {
+      id,
+      name: data.name,
+      host: data.host,
+      port: data.port,
+      user: data.user,
+      database: data.database,
+      ssl: data.ssl,
+    };
+    await invoke("validate_connection_config", { config, password: data.password });
+    await invoke("add_connection", { config, password: data.password });
     setConnections((prev) => [
       ...prev,
       {
@@ -140,7 +144,11 @@ function AppInner() {
     if (!editingConnection) return;
     const id = editingConnection.id;
     await invoke("update_connection", {
+      // Send the stored settings the dialog doesn't edit (read_only, pool size, ...);
+      // ssl_mode is left to the backend, which keeps it unless the SSL toggle changed.
       config: {
+        ...editingConnection,
+        ssl_mode: null,
         id,
         name: data.name,
         host: data.host,
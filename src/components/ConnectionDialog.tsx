@@ -1,6 +1,6 @@
 import { useState, useEffect } from "react";
 import { X, Loader2 } from "lucide-react";
-import { cn } from "@/lib/utils";
+import { cn, errorMessage } from "@/lib/utils";
 import type { ConnectionEntry } from "@/types";
 
 export interface ConnectionFormData {
@@ -83,7 +83,7 @@ export function ConnectionDialog({ open, onClose, onSubmit, editing }: Connectio
       await onSubmit(form);
       onClose();
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
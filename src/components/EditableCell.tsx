@@ -1,5 +1,5 @@
 import React, { useState, useRef, useEffect, useCallback } from "react";
-import { cn } from "@/lib/utils";
+import { cn, errorMessage } from "@/lib/utils";
 
 interface EditableCellProps {
   value: unknown;
@@ -61,7 +61,7 @@ export const EditableCell = React.memo(function EditableCell({
       await onSave(parsed);
       setEditing(false);
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setSaving(false);
     }
@@ -12,12 +12,15 @@ import {
   Trash2,
   X,
   ChevronRight,
+  AlignLeft,
+  Copy,
 } from "lucide-react";
 import { type ColumnDef } from "@tanstack/react-table";
 import { DataGrid } from "@/components/DataGrid";
 import { SqlEditor } from "@/components/SqlEditor";
 import { useToast } from "@/components/Toast";
 import type { QueryResult, HistoryEntry, SavedQuery } from "@/types";
+import { errorMessage, type CommandError } from "@/lib/utils";
 
 interface QueryEditorProps {
   connectionId: string;
@@ -26,6 +29,13 @@ interface QueryEditorProps {
 
 type PanelView = "none" | "history" | "saved";
 
+/** "N rows" for result sets, "N rows affected" for INSERT/UPDATE/DELETE and the like */
+function rowCountLabel(result: QueryResult): string {
+  const count = result.rows_affected ?? result.row_count;
+  const rows = `${count} row${count !== 1 ? "s" : ""}`;
+  return result.rows_affected != null ? `${rows} affected` : rows;
+}
+
 export function QueryEditor({ connectionId, database }: QueryEditorProps) {
   const { toast } = useToast();
   const [sql, setSql] = useState("");
@@ -51,11 +61,30 @@ export function QueryEditor({ connectionId, database }: QueryEditorProps) {
         sql: trimmed,
       });
       setResult(res);
-      toast("success", `${res.row_count} row${res.row_count !== 1 ? "s" : ""} in ${res.execution_time_ms}ms`);
+      toast("success", `${rowCountLabel(res)} in ${res.execution_time_ms}ms`);
       // Add to history (fire-and-forget)
-      invoke("add_to_history", { sql: trimmed, database }).catch(() => {});
+      invoke("add_to_history", {
+        sql: trimmed,
+        database,
+        connectionId,
+        rowCount: res.rows_affected ?? res.row_count,
+        executionTimeMs: res.execution_time_ms,
+      }).catch(() => {});
     } catch (err) {
-      setError(String(err));
+      const position = (err as Partial<CommandError>).position;
+      if (position) {
+        // Postgres positions are 1-based character offsets into the statement
+        const before = [...trimmed].slice(0, position - 1).join("").split("\n");
+        setError(`${errorMessage(err)} (line ${before.length}, column ${before[before.length - 1].length + 1})`);
+      } else {
+        setError(errorMessage(err));
+      }
+      invoke("add_to_history", {
+        sql: trimmed,
+        database,
+        connectionId,
+        error: errorMessage(err),
+      }).catch(() => {});
       setResult(null);
       toast("error", "Query failed");
     } finally {
@@ -111,6 +140,29 @@ export function QueryEditor({ connectionId, database }: QueryEditorProps) {
     }
   }, [saveName, sql, database, panelView, toast]);
 
+  const handleFormat = useCallback(async () => {
+    if (!sql.trim()) return;
+    try {
+      setSql(await invoke<string>("format_sql", { sql }));
+    } catch (err) {
+      toast("error", errorMessage(err));
+    }
+  }, [sql, toast]);
+
+  const handleCopyResult = useCallback(
+    async (format: "tsv" | "markdown") => {
+      if (!result) return;
+      try {
+        const text = await invoke<string>("format_query_result", { result, format });
+        await navigator.clipboard.writeText(text);
+        toast("success", `Copied ${result.row_count} rows as ${format === "tsv" ? "TSV" : "Markdown"}`);
+      } catch (err) {
+        toast("error", errorMessage(err));
+      }
+    },
+    [result, toast]
+  );
+
   const handleDeleteSaved = useCallback(
     async (id: string) => {
       try {
@@ -184,12 +236,30 @@ export function QueryEditor({ connectionId, database }: QueryEditorProps) {
               <>
                 <span className="flex items-center gap-1">
                   <Rows3 className="h-3 w-3" />
-                  {result.row_count} rows
+                  {rowCountLabel(result)}
                 </span>
                 <span className="flex items-center gap-1">
                   <Clock className="h-3 w-3" />
                   {result.execution_time_ms}ms
                 </span>
+                {result.columns.length > 0 && (
+                  <>
+                    <button
+                      onClick={() => handleCopyResult("tsv")}
+                      className="flex items-center gap-1 rounded px-1.5 py-0.5 hover:text-foreground transition-colors"
+                    >
+                      <Copy className="h-3 w-3" />
+                      TSV
+                    </button>
+                    <button
+                      onClick={() => handleCopyResult("markdown")}
+                      className="flex items-center gap-1 rounded px-1.5 py-0.5 hover:text-foreground transition-colors"
+                    >
+                      <Copy className="h-3 w-3" />
+                      Markdown
+                    </button>
+                  </>
+                )}
               </>
             )}
             <button
@@ -214,6 +284,15 @@ export function QueryEditor({ connectionId, database }: QueryEditorProps) {
               <Star className="h-3 w-3" />
               Saved
             </button>
+            {sql.trim() && (
+              <button
+                onClick={handleFormat}
+                className="flex items-center gap-1 rounded px-1.5 py-0.5 hover:text-foreground transition-colors"
+              >
+                <AlignLeft className="h-3 w-3" />
+                Format
+              </button>
+            )}
             {sql.trim() && (
               <button
                 onClick={() => setShowSaveInput((v) => !v)}
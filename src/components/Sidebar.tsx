@@ -17,7 +17,7 @@ import {
   RefreshCw,
   FileCode,
 } from "lucide-react";
-import { cn } from "@/lib/utils";
+import { cn, errorMessage } from "@/lib/utils";
 import type { ConnectionEntry, SchemaObject } from "@/types";
 
 interface SidebarProps {
@@ -86,6 +86,39 @@ export function Sidebar({
     return () => { cancelled = true; };
   }, [connections]);
 
+  const toggleDatabase = useCallback(
+    async (connId: string, dbName: string) => {
+      const key = `${connId}:${dbName}`;
+
+      if (expandedDatabases.has(key)) {
+        setExpandedDatabases((prev) => {
+          const next = new Set(prev);
+          next.delete(key);
+          return next;
+        });
+        return;
+      }
+
+      setExpandedDatabases((prev) => new Set(prev).add(key));
+
+      if (!schemas[key]) {
+        setLoadingDb(key);
+        try {
+          const objects = await invoke<SchemaObject[]>("get_schema", {
+            connectionId: connId,
+            database: dbName,
+          });
+          setSchemas((prev) => ({ ...prev, [key]: objects }));
+        } catch (err) {
+          setConnErrors((prev) => ({ ...prev, [key]: errorMessage(err) }));
+        } finally {
+          setLoadingDb(null);
+        }
+      }
+    },
+    [expandedDatabases, schemas]
+  );
+
   const toggleConnection = useCallback(
     async (connId: string) => {
       // If already expanded, collapse it (user can "close" even when there's an error)
@@ -115,47 +148,27 @@ export function Sidebar({
         const dbs = await invoke<string[]>("list_databases", { connectionId: connId });
         setDatabases((prev) => ({ ...prev, [connId]: dbs }));
         setHealth((prev) => ({ ...prev, [connId]: true }));
+        // Reopen the database last worked in
+        const lastDb = connections.find((c) => c.id === connId)?.last_database;
+        if (lastDb && dbs.includes(lastDb) && !expandedDatabases.has(`${connId}:${lastDb}`)) {
+          toggleDatabase(connId, lastDb);
+        }
       } catch (err) {
-        setConnErrors((prev) => ({ ...prev, [connId]: String(err) }));
+        setConnErrors((prev) => ({ ...prev, [connId]: errorMessage(err) }));
         setHealth((prev) => ({ ...prev, [connId]: false }));
       } finally {
         setLoadingConn(null);
       }
     },
-    [expandedConnections, databases, connErrors, onSelectConnection]
-  );
-
-  const toggleDatabase = useCallback(
-    async (connId: string, dbName: string) => {
-      const key = `${connId}:${dbName}`;
-
-      if (expandedDatabases.has(key)) {
-        setExpandedDatabases((prev) => {
-          const next = new Set(prev);
-          next.delete(key);
-          return next;
-        });
-        return;
-      }
-
-      setExpandedDatabases((prev) => new Set(prev).add(key));
-
-      if (!schemas[key]) {
-        setLoadingDb(key);
-        try {
-          const objects = await invoke<SchemaObject[]>("get_schema", {
-            connectionId: connId,
-            database: dbName,
-          });
-          setSchemas((prev) => ({ ...prev, [key]: objects }));
-        } catch (err) {
-          setConnErrors((prev) => ({ ...prev, [key]: String(err) }));
-        } finally {
-          setLoadingDb(null);
-        }
-      }
-    },
-    [expandedDatabases, schemas]
+    [
+      expandedConnections,
+      databases,
+      connErrors,
+      onSelectConnection,
+      connections,
+      expandedDatabases,
+      toggleDatabase,
+    ]
   );
 
   // Auto-expand newly added connections
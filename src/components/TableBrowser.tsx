@@ -6,9 +6,13 @@ import { DataGrid } from "@/components/DataGrid";
 import { EditableCell } from "@/components/EditableCell";
 import { useToast } from "@/components/Toast";
 import type { QueryResult, ColumnInfo } from "@/types";
+import { errorMessage } from "@/lib/utils";
 
 const PAGE_SIZE = 100;
 
+/** Quote an identifier, doubling embedded double quotes */
+const quoteIdent = (name: string) => `"${name.replace(/"/g, '""')}"`;
+
 type SortDirection = "asc" | "desc" | null;
 interface SortState {
   column: string;
@@ -27,6 +31,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
   const [rows, setRows] = useState<unknown[][]>([]);
   const [columnNames, setColumnNames] = useState<string[]>([]);
   const [columnTypes, setColumnTypes] = useState<Map<string, string>>(new Map());
+  const [generatedColumns, setGeneratedColumns] = useState<Set<string>>(new Set());
   const [primaryKeyColumns, setPrimaryKeyColumns] = useState<string[]>([]);
   const [totalCount, setTotalCount] = useState<number | null>(null);
   const [execTime, setExecTime] = useState(0);
@@ -50,12 +55,12 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
       if (!trimmed) continue;
       // Support: "NULL", "NOT NULL", or ILIKE pattern
       if (trimmed.toLowerCase() === "null") {
-        parts.push(`"${col}" IS NULL`);
+        parts.push(`${quoteIdent(col)} IS NULL`);
       } else if (trimmed.toLowerCase() === "not null") {
-        parts.push(`"${col}" IS NOT NULL`);
+        parts.push(`${quoteIdent(col)} IS NOT NULL`);
       } else {
         // Use ILIKE for text search; cast to text for non-text columns
-        parts.push(`"${col}"::text ILIKE '%${trimmed.replace(/'/g, "''")}%'`);
+        parts.push(`${quoteIdent(col)}::text ILIKE '%${trimmed.replace(/'/g, "''")}%'`);
       }
     }
     return parts.length > 0 ? ` WHERE ${parts.join(" AND ")}` : "";
@@ -63,18 +68,18 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
 
   const buildOrderClause = useCallback((): string => {
     if (!sort.column || !sort.direction) return "";
-    return ` ORDER BY "${sort.column}" ${sort.direction.toUpperCase()}`;
+    return ` ORDER BY ${quoteIdent(sort.column)} ${sort.direction.toUpperCase()}`;
   }, [sort]);
 
   const buildSelectSql = useCallback(
     (limit: number, offset = 0): string => {
-      return `SELECT * FROM "${schema}"."${table}"${buildWhereClause()}${buildOrderClause()} LIMIT ${limit} OFFSET ${offset}`;
+      return `SELECT * FROM ${quoteIdent(schema)}.${quoteIdent(table)}${buildWhereClause()}${buildOrderClause()} LIMIT ${limit} OFFSET ${offset}`;
     },
     [schema, table, buildWhereClause, buildOrderClause]
   );
 
   const buildCountSql = useCallback((): string => {
-    return `SELECT COUNT(*) FROM "${schema}"."${table}"${buildWhereClause()}`;
+    return `SELECT COUNT(*) FROM ${quoteIdent(schema)}.${quoteIdent(table)}${buildWhereClause()}`;
   }, [schema, table, buildWhereClause]);
 
   // Load metadata (columns, PK) — only when the table changes
@@ -87,6 +92,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
       setRows([]);
       setColumnNames([]);
       setColumnTypes(new Map());
+      setGeneratedColumns(new Set());
       setPrimaryKeyColumns([]);
       setTotalCount(null);
       setShowAddRow(false);
@@ -100,12 +106,12 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
           invoke<QueryResult>("execute_query", {
             connectionId,
             database,
-            sql: `SELECT * FROM "${schema}"."${table}" LIMIT ${PAGE_SIZE}`,
+            sql: `SELECT * FROM ${quoteIdent(schema)}.${quoteIdent(table)} LIMIT ${PAGE_SIZE}`,
           }),
           invoke<QueryResult>("execute_query", {
             connectionId,
             database,
-            sql: `SELECT COUNT(*) FROM "${schema}"."${table}"`,
+            sql: `SELECT COUNT(*) FROM ${quoteIdent(schema)}.${quoteIdent(table)}`,
           }),
           invoke<ColumnInfo[]>("get_columns", {
             connectionId,
@@ -137,9 +143,10 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
           typeMap.set(col.name, col.data_type);
         }
         setColumnTypes(typeMap);
+        setGeneratedColumns(new Set(colInfo.filter((c) => c.is_generated).map((c) => c.name)));
         setPrimaryKeyColumns(pkCols);
       } catch (err) {
-        if (!cancelled) setError(String(err));
+        if (!cancelled) setError(errorMessage(err));
       } finally {
         if (!cancelled) setLoading(false);
       }
@@ -184,7 +191,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
           setTotalCount(Number(cnt));
         }
       } catch (err) {
-        if (!cancelled) setError(String(err));
+        if (!cancelled) setError(errorMessage(err));
       }
     }
 
@@ -206,7 +213,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
       });
       setRows((prev) => [...prev, ...res.rows]);
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setLoadingMore(false);
     }
@@ -223,18 +230,14 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
   }, []);
 
   const handleInsert = useCallback(async () => {
-    const columns: string[] = [];
-    const values: (string | number | boolean | null)[] = [];
-    const types: string[] = [];
+    // Columns left blank are omitted so the database applies their defaults.
+    const values: Record<string, string | number | boolean | null> = {};
     for (const col of columnNames) {
       const raw = (draftValues[col] ?? "").trim();
       if (raw === "") continue;
-      const parsed = parseCellValue(raw);
-      columns.push(col);
-      values.push(parsed);
-      types.push(columnTypes.get(col) ?? "text");
+      values[col] = parseCellValue(raw);
     }
-    if (columns.length === 0) {
+    if (Object.keys(values).length === 0) {
       setInsertError("Fill in at least one column");
       return;
     }
@@ -246,9 +249,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
         database,
         schema,
         table,
-        columns,
         values,
-        columnTypes: types,
       });
       setShowAddRow(false);
       setDraftValues({});
@@ -263,7 +264,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
       setRows(res.rows);
       toast("success", "Row inserted");
     } catch (err) {
-      setInsertError(String(err));
+      setInsertError(errorMessage(err));
       toast("error", "Insert failed");
     } finally {
       setInserting(false);
@@ -274,7 +275,6 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
     schema,
     table,
     columnNames,
-    columnTypes,
     draftValues,
     parseCellValue,
     buildSelectSql,
@@ -305,7 +305,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
         schema,
         table,
         primaryKeyColumns,
-        primaryKeyValuesList: pkValuesList,
+        rows: pkValuesList,
       });
       setRowSelection({});
       const indicesToRemove = new Set(
@@ -323,7 +323,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
       setTotalCount((c) => (c !== null ? Math.max(0, c - selectedIds.length) : null));
       toast("success", `Deleted ${selectedIds.length} row${selectedIds.length > 1 ? "s" : ""}`);
     } catch (err) {
-      setError(String(err));
+      setError(errorMessage(err));
       toast("error", "Delete failed");
     } finally {
       setDeleting(false);
@@ -384,6 +384,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
         primaryKeyColumns,
         primaryKeyValues: pkValues,
         newValue,
+        setNull: newValue === null,
       });
       const colIndex = columnNames.indexOf(columnName);
       if (colIndex === -1) return;
@@ -467,7 +468,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
     const canEdit = primaryKeyColumns.length > 0;
     const dataCols = columnNames.map((col) => {
       const isPk = primaryKeyColumns.includes(col);
-      const editable = canEdit && !isPk;
+      const editable = canEdit && !isPk && !generatedColumns.has(col);
       const colSort = sort.column === col ? sort.direction : null;
       return {
         accessorKey: col,
@@ -527,6 +528,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
   }, [
     columnNames,
     columnTypes,
+    generatedColumns,
     primaryKeyColumns,
     handleCellSave,
     selectionColumn,
@@ -661,7 +663,7 @@ export function TableBrowser({ connectionId, database, schema, table }: TableBro
             <div className="mb-2 text-xs text-destructive">{insertError}</div>
           )}
           <div className="flex flex-wrap items-end gap-3">
-            {columnNames.map((col) => (
+            {columnNames.filter((col) => !generatedColumns.has(col)).map((col) => (
               <div key={col} className="flex flex-col gap-0.5">
                 <label className="text-[10px] font-medium text-muted-foreground">
                   {col}
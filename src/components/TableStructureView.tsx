@@ -2,6 +2,7 @@ import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { Loader2, AlertCircle, Key, Hash, Link, ShieldCheck } from "lucide-react";
 import type { TableStructure } from "@/types";
+import { errorMessage } from "@/lib/utils";
 
 interface TableStructureViewProps {
   connectionId: string;
@@ -34,7 +35,7 @@ export function TableStructureView({
         });
         if (!cancelled) setStructure(res);
       } catch (err) {
-        if (!cancelled) setError(String(err));
+        if (!cancelled) setError(errorMessage(err));
       } finally {
         if (!cancelled) setLoading(false);
       }
@@ -93,7 +94,13 @@ export function TableStructureView({
                   )}
                 </td>
                 <td className="py-1.5 font-mono text-muted-foreground">
-                  {col.default_value ?? <span className="italic">none</span>}
+                  {col.generation_expression ? (
+                    `GENERATED ALWAYS AS (${col.generation_expression})`
+                  ) : col.is_auto_increment ? (
+                    <span className="italic">auto increment</span>
+                  ) : (
+                    col.default_value ?? <span className="italic">none</span>
+                  )}
                 </td>
               </tr>
             ))}
@@ -1,5 +1,6 @@
 import { useState, useCallback } from "react";
 import { invoke, type InvokeArgs } from "@tauri-apps/api/core";
+import { errorMessage } from "@/lib/utils";
 
 interface UseInvokeResult<T> {
   data: T | null;
@@ -26,8 +27,7 @@ export function useInvoke<T>(): UseInvokeResult<T> {
         setData(result);
         return result;
       } catch (err) {
-        const message = err instanceof Error ? err.message : String(err);
-        setError(message);
+        setError(errorMessage(err));
         return null;
       } finally {
         setLoading(false);
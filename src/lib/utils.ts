@@ -4,3 +4,23 @@ import { twMerge } from "tailwind-merge";
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+/** Error returned by a Tauri command (serialized AppError) */
+export interface CommandError {
+  /** AppError variant; "read_only" is a local refusal on a read-only connection, not a server error */
+  kind: "database" | "connection" | "config" | "keychain" | "read_only";
+  message: string;
+  /** SQLSTATE, for database errors */
+  code: string | null;
+  /** 1-based character offset of the error in the SQL, when Postgres reports one */
+  position: number | null;
+}
+
+/** Human-readable message for anything thrown by invoke() or elsewhere */
+export function errorMessage(err: unknown): string {
+  if (err instanceof Error) return err.message;
+  if (typeof err === "object" && err !== null && "message" in err) {
+    return String((err as CommandError).message);
+  }
+  return String(err);
+}
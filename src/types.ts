@@ -2,15 +2,31 @@
 export interface SchemaObject {
   name: string;
   schema: string;
-  object_type: "table" | "view" | "function";
+  object_type: "table" | "view" | "materialized_view" | "function" | "sequence";
 }
 
 /** Matches Rust QueryResult */
 export interface QueryResult {
   columns: string[];
+  /** Server type name per column, e.g. "TIMESTAMPTZ" vs "TIMESTAMP" */
+  column_types?: string[];
   rows: (string | number | boolean | null)[][];
   row_count: number;
   execution_time_ms: number;
+  total_estimate?: number | null;
+  rows_affected?: number | null;
+  /** More rows existed than the max_rows cap returned */
+  truncated?: boolean;
+  /** Plain single-table SELECT including the table's primary key; rows can be edited */
+  editable?: boolean;
+  primary_key_columns?: string[];
+  source_schema?: string | null;
+  source_table?: string | null;
+}
+
+export interface ScriptResult {
+  results: QueryResult[];
+  error: string | null;
 }
 
 /** Matches Rust ColumnInfo */
@@ -19,6 +35,8 @@ export interface ColumnInfo {
   data_type: string;
   is_nullable: boolean;
   is_primary_key: boolean;
+  /** Generated column; not writable */
+  is_generated?: boolean;
 }
 
 /** Detailed column info for DDL view */
@@ -26,7 +44,18 @@ export interface ColumnDetail {
   name: string;
   data_type: string;
   is_nullable: boolean;
+  /** null for sequence defaults; see is_auto_increment */
   default_value: string | null;
+  /** column_default as stored, including nextval(...) */
+  raw_default?: string | null;
+  /** Identity column or sequence default (serial) */
+  is_auto_increment?: boolean;
+  comment: string | null;
+  /** "ALWAYS" or "BY DEFAULT" for identity columns */
+  identity_generation?: string | null;
+  /** Expression of a generated column */
+  generation_expression?: string | null;
+  is_generated?: boolean;
 }
 
 /** Index info for DDL view */
@@ -53,12 +82,23 @@ export interface ForeignKeyInfo {
   ref_column: string;
 }
 
+/** Trigger info for DDL view */
+export interface TriggerInfo {
+  name: string;
+  /** BEFORE, AFTER, or INSTEAD OF */
+  timing: string;
+  events: string[];
+  definition: string;
+}
+
 /** Full table structure for DDL view */
 export interface TableStructure {
   columns: ColumnDetail[];
   indexes: IndexInfo[];
   constraints: ConstraintInfo[];
   foreign_keys: ForeignKeyInfo[];
+  triggers: TriggerInfo[];
+  table_comment: string | null;
 }
 
 /** A tab in the main area */
@@ -78,7 +118,28 @@ export interface Tab {
 export interface HistoryEntry {
   sql: string;
   database: string;
+  connection_id: string | null;
   executed_at: string;
+  row_count: number | null;
+  execution_time_ms: number | null;
+  success: boolean;
+  error: string | null;
+}
+
+/** User settings (settings.json) */
+export interface Settings {
+  /** History entries to keep; 0 = unlimited */
+  max_history: number;
+  /** application_name sent to the server unless a connection overrides it */
+  application_name: string;
+  /** Open pools kept before idle secondary-database pools are closed (LRU); 0 = unlimited */
+  max_pools: number;
+  /** Seconds between background connection liveness checks; 0 = off */
+  heartbeat_interval_secs: number;
+  /** Extra connect attempts after a transient failure (timeout, refused) */
+  connect_retries: number;
+  /** Wait before the first retry, doubled for each one after */
+  connect_retry_backoff_ms: number;
 }
 
 /** Saved / favorite query */
@@ -87,6 +148,9 @@ export interface SavedQuery {
   name: string;
   sql: string;
   database: string;
+  created_at: string | null;
+  updated_at: string | null;
+  tags: string[];
 }
 
 /** A connected database shown in the sidebar */
@@ -98,4 +162,172 @@ export interface ConnectionEntry {
   user: string;
   database: string;
   ssl: boolean;
+  /** Edits are refused and sessions default to read-only transactions */
+  read_only?: boolean;
+  /** Pool size (default 5) */
+  max_connections?: number;
+  /** Seconds to wait for a pooled connection (default 5) */
+  acquire_timeout_secs?: number;
+  /** Non-primary database opened most recently; reopened on reconnect */
+  last_database?: string;
+}
+
+/** On-disk size of a table in bytes (total includes TOAST) */
+export interface TableSize {
+  total_bytes: number;
+  table_bytes: number;
+  index_bytes: number;
+}
+
+/** On-disk size of a database in bytes (list_database_sizes) */
+export interface DatabaseSize {
+  name: string;
+  bytes: number;
+}
+
+/** An installed extension (list_extensions) */
+export interface ExtensionInfo {
+  name: string;
+  version: string;
+  schema: string;
+}
+
+/** An enum type and its values in sort order */
+export interface EnumType {
+  schema: string;
+  name: string;
+  values: string[];
+}
+
+/** One cell change for update_cells; null new_value sets NULL */
+export interface CellEdit {
+  column: string;
+  primary_key_columns: string[];
+  primary_key_values: unknown[];
+  new_value: unknown;
+}
+
+/** Unsaved connection and its password (parse_connection_uri, load_pgpass_connections) */
+export interface ConnectionDraft {
+  config: ConnectionEntry & { ssl_mode: string | null; ssl_root_cert?: string };
+  password: string;
+}
+
+/** Result of load_config_connections; errors are [name, message] pairs */
+export interface ConnectionLoadResult {
+  connections: ConnectionEntry[];
+  errors: [string, string][];
+}
+
+/** Server version and key settings (get_server_info) */
+export interface ServerInfo {
+  version: string;
+  /** server_version_num, e.g. 160002 */
+  version_num: number;
+  encoding: string;
+  timezone: string;
+  max_connections: number;
+}
+
+/** A server backend from pg_stat_activity (list_active_sessions) */
+export interface SessionInfo {
+  pid: number;
+  usename: string | null;
+  datname: string | null;
+  application_name: string | null;
+  client_addr: string | null;
+  state: string | null;
+  query: string | null;
+  /** RFC 3339 */
+  query_start: string | null;
+  /** The session making the request */
+  is_current: boolean;
+}
+
+/** Payload of the "pg-notification" event (see listen/unlisten) */
+export interface PgNotification {
+  connection_id: string;
+  channel: string;
+  payload: string;
+  /** Pid of the sending backend */
+  process_id: number;
+}
+
+/** Planner estimate for a query's root plan node (estimate_query_cost) */
+export interface CostEstimate {
+  /** In the planner's arbitrary cost units */
+  total_cost: number;
+  plan_rows: number;
+}
+
+/** One statement's result in dry_run_script */
+export interface StatementOutcome {
+  statement: string;
+  success: boolean;
+  /** null for statements returning rows, or that failed */
+  rows_affected: number | null;
+  error: string | null;
+}
+
+/** Where connection passwords are stored (password_backend): the OS keyring, or an
+ * encrypted file in the config directory when no keyring is available */
+export type SecretBackend = "keyring" | "file";
+
+/** Table maintenance operation for maintain_table */
+export type MaintenanceOp = "vacuum" | "vacuum_full" | "analyze" | "reindex";
+
+/** Comparison operator of a ColumnFilter */
+export type FilterOp = "eq" | "neq" | "gt" | "gte" | "lt" | "lte" | "like" | "is_null" | "is_not_null";
+
+/** A `column <op> value` condition; value is bound as a parameter and ignored for
+ * is_null / is_not_null (browse_table, bulk_update) */
+export interface ColumnFilter {
+  column: string;
+  op: FilterOp;
+  value?: unknown;
+}
+
+/** An open query tab saved by save_session */
+export interface TabState {
+  connection_id: string;
+  database: string;
+  sql: string;
+  title: string;
+}
+
+/** An item in both tables of a SchemaDiff with the same name but different definitions */
+export interface ItemChange<T> {
+  name: string;
+  before: T;
+  after: T;
+}
+
+/** Structural differences from table a to table b (diff_table_structure): "added" items
+ * exist only in b, "removed" ones only in a */
+export interface SchemaDiff {
+  added_columns: ColumnDetail[];
+  removed_columns: ColumnDetail[];
+  changed_columns: ItemChange<ColumnDetail>[];
+  added_indexes: IndexInfo[];
+  removed_indexes: IndexInfo[];
+  changed_indexes: ItemChange<IndexInfo>[];
+  added_constraints: ConstraintInfo[];
+  removed_constraints: ConstraintInfo[];
+  changed_constraints: ItemChange<ConstraintInfo>[];
+}
+
+/** A session waiting on a lock and one session blocking it (list_blocking_locks) */
+export interface LockInfo {
+  blocked_pid: number;
+  blocked_user: string | null;
+  blocked_query: string | null;
+  /** RFC 3339 */
+  blocked_since: string | null;
+  blocking_pid: number;
+  blocking_user: string | null;
+  blocking_query: string | null;
+  blocking_state: string | null;
+  lock_type: string | null;
+  lock_mode: string | null;
+  relation: string | null;
 }